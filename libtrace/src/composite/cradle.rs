@@ -16,6 +16,16 @@ pub struct NewtonCradle {
 
 impl NewtonCradle {
     pub fn build(self) -> Composite {
+        self.assemble(None)
+    }
+
+    /// Cradle whose balls go through the positions given at each instant, with the same
+    /// meaning as `pos` (which is ignored), so that they are blurred by the camera shutter
+    pub fn build_moving(self, frames: &[(f64, [f64; 5])]) -> Composite {
+        self.assemble(Some(frames))
+    }
+
+    fn assemble(self, frames: Option<&[(f64, [f64; 5])]>) -> Composite {
         let plastic = Texture::Lambertian(RGB(0.1, 0.1, 0.1));
         let steel = Texture::Metal(RGB(0.8, 0.6, 0.2), 0.0);
         let nylon = Texture::Lambertian(RGB(0.9, 0.9, 0.9));
@@ -71,8 +81,9 @@ impl NewtonCradle {
         let smoothtop4 = make_cap(self.a + v * 0.9 + u * 0.1 + w);
         let r = u.len() * 0.7 / 10.0;
 
-        let make_ball = |c: Vec3, swing: f64| {
+        let make_ball = |idx: usize, c: Vec3, swing: f64| {
             let axis = Vec3(c.0, w.1, c.2);
+            let swing = if frames.is_some() { 0. } else { swing };
             let theta = swing * std::f64::consts::PI / 180.;
             let radius = (c - axis).len() / w.len();
             let local_w = v * radius * theta.sin() + w * radius * theta.cos();
@@ -100,11 +111,31 @@ impl NewtonCradle {
                 texture: nylon,
             }
             .build();
+            let parts = (sphere.build(), ring.build(), threada, threadb);
+            let animate = |p: Primitive| match frames {
+                None => p.wrap(),
+                Some(frames) => {
+                    // Each ball swings around the bar that holds it
+                    let keys = frames
+                        .iter()
+                        .map(|(time, pos)| {
+                            let rotation = Quat::axis_angle(u, pos[idx]);
+                            Keyframe::new(*time, axis - rotation.rotate(axis), rotation)
+                        })
+                        .collect();
+                    Moving {
+                        obj: p,
+                        motion: Motion::keyframes(keys),
+                    }
+                    .build()
+                    .wrap()
+                }
+            };
             (
-                sphere.build().wrap(),
-                ring.build().wrap(),
-                threada.wrap(),
-                threadb.wrap(),
+                animate(parts.0),
+                animate(parts.1),
+                animate(parts.2),
+                animate(parts.3),
             )
         };
         let pos = match self.pos {
@@ -112,15 +143,15 @@ impl NewtonCradle {
             Some(arr) => arr,
         };
         let (sphere1, ring1, thread1a, thread1b) =
-            make_ball(self.a + u * 0.5 + v * 0.15 + v.unit() * 9. * r + w * 0.3, pos[0]);
+            make_ball(0, self.a + u * 0.5 + v * 0.15 + v.unit() * 9. * r + w * 0.3, pos[0]);
         let (sphere2, ring2, thread2a, thread2b) =
-            make_ball(self.a + u * 0.5 + v * 0.15 + v.unit() * 7. * r + w * 0.3, pos[1]);
+            make_ball(1, self.a + u * 0.5 + v * 0.15 + v.unit() * 7. * r + w * 0.3, pos[1]);
         let (sphere3, ring3, thread3a, thread3b) =
-            make_ball(self.a + u * 0.5 + v * 0.15 + v.unit() * 5. * r + w * 0.3, pos[2]);
+            make_ball(2, self.a + u * 0.5 + v * 0.15 + v.unit() * 5. * r + w * 0.3, pos[2]);
         let (sphere4, ring4, thread4a, thread4b) =
-            make_ball(self.a + u * 0.5 + v * 0.15 + v.unit() * 3. * r + w * 0.3, pos[3]);
+            make_ball(3, self.a + u * 0.5 + v * 0.15 + v.unit() * 3. * r + w * 0.3, pos[3]);
         let (sphere5, ring5, thread5a, thread5b) =
-            make_ball(self.a + u * 0.5 + v * 0.15 + v.unit() * 1. * r + w * 0.3, pos[4]);
+            make_ball(4, self.a + u * 0.5 + v * 0.15 + v.unit() * 1. * r + w * 0.3, pos[4]);
         vec![
             pedestal, pillar1, pillar2, pillar3, pillar4, bar1, bar2, smoothtop1, smoothtop2,
             smoothtop3, smoothtop4, sphere1, sphere2, sphere3, sphere4, sphere5, ring1, ring2,
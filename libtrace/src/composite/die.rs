@@ -34,8 +34,9 @@ impl Die {
                 * len
         };
         let v1 = w.cross(u1).unit() * len;
-        let v = u1 * self.rot.cos() + v1 * self.rot.sin();
-        let u = -u1 * self.rot.sin() + v1 * self.rot.cos();
+        let rot = Quat::axis_angle(w, self.rot.to_degrees());
+        let v = rot.rotate(u1);
+        let u = rot.rotate(v1);
 
         let mut die: Interaction = Rhomboid {
             a: self.a,
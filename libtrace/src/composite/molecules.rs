@@ -30,18 +30,27 @@ pub struct Molecule {
 /// Instead of creating `MoleculeObject`s, it is recommended to build them using a `Molecule`.
 #[derive(Clone)]
 pub struct MoleculeObject {
-    pub atoms: Vec<Sphere>,
+    /// Atoms of the same kind are copies of a single sphere
+    pub atoms: Vec<Instance>,
     pub links: Vec<EmptyCylinder>,
 }
 
 /// Creates an atom-building closure from the radius and texture of the atoms to be made.
 ///
-/// The resulting closure takes the center of the sphere.
-fn atom_builder(r: f64, texture: Texture) -> Box<dyn Fn(Vec3) -> Sphere> {
-    Box::new(move |u| Sphere {
-        center: u,
+/// The resulting closure takes the center of the sphere, all of its atoms share
+/// the same geometry.
+fn atom_builder(r: f64, texture: Texture) -> Box<dyn Fn(Vec3) -> Instance> {
+    let proto = Prototype::new(vec![Sphere {
+        center: Vec3(0., 0., 0.),
         radius: r,
         texture,
+    }
+    .build()
+    .wrap()]);
+    Box::new(move |u| Instance {
+        proto: proto.clone(),
+        transform: Transform::translation(u),
+        texture: None,
     })
 }
 
@@ -139,7 +148,8 @@ impl Molecule {
                 }
             };
             let xx = zz.cross(yy).unit();
-            (xx * self.rot.cos() + yy * self.rot.sin(), yy * self.rot.cos() - xx * self.rot.sin())
+            let rot = Quat::axis_angle(-zz, self.rot.to_degrees());
+            (rot.rotate(xx), rot.rotate(yy))
         };
         let x = zz;
         let v = xx * 0.00 + yy * 0.87 + zz * 0.50;
@@ -0,0 +1,116 @@
+use crate::internal::*;
+
+/// Axis-aligned bounding box
+#[derive(Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Smallest box that contains all of the given points
+    pub fn around(pts: &[Vec3]) -> Self {
+        let mut res = Self {
+            min: Vec3(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Vec3(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        };
+        for p in pts {
+            res.min = Vec3(res.min.0.min(p.0), res.min.1.min(p.1), res.min.2.min(p.2));
+            res.max = Vec3(res.max.0.max(p.0), res.max.1.max(p.1), res.max.2.max(p.2));
+        }
+        res
+    }
+
+    /// Smallest box that contains both boxes
+    pub fn union(self, other: Self) -> Self {
+        Self::around(&[self.min, self.max, other.min, other.max])
+    }
+
+    /// Common part of both boxes, which is empty (`min` bigger than `max`) if they are disjoint
+    pub fn intersection(self, other: Self) -> Self {
+        Self {
+            min: Vec3(
+                self.min.0.max(other.min.0),
+                self.min.1.max(other.min.1),
+                self.min.2.max(other.min.2),
+            ),
+            max: Vec3(
+                self.max.0.min(other.max.0),
+                self.max.1.min(other.max.1),
+                self.max.2.min(other.max.2),
+            ),
+        }
+    }
+
+    /// Smallest box that contains the image of this box by a transformation
+    pub fn transform(self, m: &Mat4) -> Self {
+        let mut corners = Vec::new();
+        for &x in &[self.min.0, self.max.0] {
+            for &y in &[self.min.1, self.max.1] {
+                for &z in &[self.min.2, self.max.2] {
+                    corners.push(m.apply_point(Vec3(x, y, z)));
+                }
+            }
+        }
+        Self::around(&corners)
+    }
+
+    /// Whether the box contains no point at all, as a result of `intersection`
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1 || self.min.2 > self.max.2
+    }
+
+    /// Slightly bigger box, to account for rounding errors
+    pub fn pad(self, e: f64) -> Self {
+        let e = Vec3(e, e, e);
+        Self {
+            min: self.min - e,
+            max: self.max + e,
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.
+    }
+
+    /// Extent along each axis
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, p: Vec3) -> bool {
+        self.min.0 <= p.0
+            && p.0 <= self.max.0
+            && self.min.1 <= p.1
+            && p.1 <= self.max.1
+            && self.min.2 <= p.2
+            && p.2 <= self.max.2
+    }
+
+    /// Slab test: values of `t` between which the ray is inside the box, if any.
+    pub fn range(&self, r: &Ray) -> Option<(f64, f64)> {
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+        for (o, d, lo, hi) in &[
+            (r.orig.0, r.dir.0, self.min.0, self.max.0),
+            (r.orig.1, r.dir.1, self.min.1, self.max.1),
+            (r.orig.2, r.dir.2, self.min.2, self.max.2),
+        ] {
+            if d.abs() < EPSILON {
+                if o < lo || o > hi {
+                    return None;
+                }
+            } else {
+                let t1 = (lo - o) / d;
+                let t2 = (hi - o) / d;
+                tmin = tmin.max(t1.min(t2));
+                tmax = tmax.min(t1.max(t2));
+            }
+        }
+        if tmin <= tmax {
+            Some((tmin, tmax))
+        } else {
+            None
+        }
+    }
+}
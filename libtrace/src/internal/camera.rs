@@ -1,4 +1,68 @@
 use crate::internal::*;
+use std::f64::consts::PI;
+
+/// How directions of the scene are mapped to positions on the image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Pinhole camera, straight lines remain straight
+    Perspective,
+    /// Parallel rays, the size of objects does not depend on their distance; the view
+    /// is as wide as the perspective one at the distance of the target
+    Orthographic,
+    /// Angle to the axis proportional to the distance to the center of the image,
+    /// with the field of view reached at the top and bottom edges
+    Fisheye,
+    /// Full 360° panorama: longitude along the width, latitude along the height
+    Equirectangular,
+}
+
+impl Projection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "perspective" => Some(Projection::Perspective),
+            "orthographic" => Some(Projection::Orthographic),
+            "fisheye" => Some(Projection::Fisheye),
+            "equirectangular" => Some(Projection::Equirectangular),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective => "perspective",
+            Projection::Orthographic => "orthographic",
+            Projection::Fisheye => "fisheye",
+            Projection::Equirectangular => "equirectangular",
+        }
+    }
+}
+
+/// How the eyes of a stereo rig look at the point where their views coincide
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Convergence {
+    /// Both eyes are turned towards the convergence point, which is simple but distorts
+    /// the edges of the images
+    ToeIn,
+    /// Parallel eyes with shifted images, usually more comfortable
+    OffAxis,
+}
+
+impl Convergence {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "toe-in" => Some(Convergence::ToeIn),
+            "off-axis" => Some(Convergence::OffAxis),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Convergence::ToeIn => "toe-in",
+            Convergence::OffAxis => "off-axis",
+        }
+    }
+}
 
 /// Abstraction for the point of view chosen to take the scene from
 #[derive(Clone)]
@@ -11,6 +75,25 @@ pub struct Camera {
     horiz: Vec3,
     /// Direction of the left edge of the view
     vert: Vec3,
+    /// Radius of the lens, zero for a pinhole camera where everything is sharp
+    lens: f64,
+    /// Distance from `orig` at which objects are sharp
+    focus: f64,
+    /// Distance from `orig` to the point aimed at, used for autofocus
+    target_dist: f64,
+    /// Whether `focus` is `target_dist` rather than a distance given to `set_lens`
+    autofocus: bool,
+    projection: Projection,
+    /// Half of the vertical field of view (radians), kept for the fisheye projection
+    /// which accepts angles of 180° and more
+    half_fov: f64,
+    /// Signed distance from the eye to the center of the rig for omni-directional stereo
+    /// panoramas, where each direction is seen from a different point of a circle
+    ods: f64,
+    /// Instants at which the shutter opens and closes
+    shutter: (f64, f64),
+    /// Movement of the whole camera while the shutter is open
+    motion: Option<Motion>,
 }
 
 impl Camera {
@@ -23,24 +106,42 @@ impl Camera {
     /// vfov: field of view (degrees)
     ///
     /// ratio: width/height aspect ratio
+    ///
+    /// projection: mapping from directions to the image
     pub fn new_absolute(
         eye: Vec3,
         target: Vec3,
         vert: Vec3,
         vfov: f64,
         ratio: f64,
+        projection: Projection,
     ) -> Self {
         let theta = vfov * std::f64::consts::PI / 180.;
-        let half_hgt = (theta / 2.).tan();
+        // Fisheye and equirectangular rays only depend on the directions of the edges
+        // and on the ratio, the tangent would change sign past 180°
+        let half_hgt = match projection {
+            Projection::Perspective | Projection::Orthographic => (theta / 2.).tan(),
+            Projection::Fisheye | Projection::Equirectangular => theta / 2.,
+        };
         let half_wth = ratio * half_hgt;
         let w = (eye - target).unit();
         let u = vert.cross(w);
         let v = w.cross(u);
+        let dist = (eye - target).len();
         Camera {
             orig: eye,
             low_left: eye - u * half_wth - v * half_hgt - w,
             horiz: u * 2. * half_wth,
             vert: v * 2. * half_hgt,
+            lens: 0.,
+            focus: dist,
+            target_dist: dist,
+            autofocus: true,
+            projection,
+            half_fov: theta / 2.,
+            ods: 0.,
+            shutter: (0., 0.),
+            motion: None,
         }
     }
 
@@ -53,6 +154,8 @@ impl Camera {
     /// tilt: 0 for vertical field of view
     /// aperture: vertical field of view (degrees)
     /// ratio: width/height aspect ratio
+    /// projection: mapping from directions to the image
+    #[allow(clippy::too_many_arguments)]
     pub fn new_relative(
         target: Vec3,
         angle: f64,
@@ -61,40 +164,330 @@ impl Camera {
         tilt: f64,
         aperture: f64,
         ratio: f64,
+        projection: Projection,
     ) -> Self {
-        let theta = aperture * std::f64::consts::PI / 180.;
-        let half_hgt = (theta / 2.).tan();
+        // The eye is sin(rise) above the target for each unit of horizontal distance
+        let elevation = rise.to_radians().sin().atan().to_degrees();
+        // Looking along -z from (0, 0, dist), then rolled, raised and turned around
+        let frame = Transform::translation(target) * Transform::euler(angle, -elevation, -tilt);
+        Self::new_absolute(
+            frame.apply_point(Vec3(0., 0., dist)),
+            target,
+            frame.apply_vector(Vec3(0., 1., 0.)),
+            aperture,
+            ratio,
+            projection,
+        )
+    }
+
+    /// Camera aimed at the center of a box from the given direction, as close as
+    /// possible while the whole box stays in view
+    ///
+    /// See `new_relative` for the parameters, and `world::bounds_of_bounded` for the box
+    /// of a scene or of a `Composite`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn frame_object(
+        bounds: Aabb,
+        angle: f64,
+        rise: f64,
+        tilt: f64,
+        aperture: f64,
+        ratio: f64,
+        projection: Projection,
+    ) -> Self {
+        let dist = Self::fit_distance(bounds, angle, rise, tilt, aperture, ratio, projection);
+        let target = (bounds.min + bounds.max) * 0.5;
+        Self::new_relative(target, angle, rise, dist, tilt, aperture, ratio, projection)
+    }
+
+    /// Distance to the center of the box used by `frame_object`
+    ///
+    /// The corners of the box touch the edges of the view for the perspective and
+    /// orthographic projections, the other ones fit the sphere around the box.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit_distance(
+        bounds: Aabb,
+        angle: f64,
+        rise: f64,
+        tilt: f64,
+        aperture: f64,
+        ratio: f64,
+        projection: Projection,
+    ) -> f64 {
+        let center = (bounds.min + bounds.max) * 0.5;
+        // Same orientation at distance 1
+        let probe = Self::new_relative(center, angle, rise, 1., tilt, aperture, ratio, projection);
+        let w = probe.orig - center;
+        let u = probe.horiz.unit();
+        let v = probe.vert.unit();
+        let half_hgt = (aperture * std::f64::consts::PI / 360.).tan();
         let half_wth = ratio * half_hgt;
-        let eye = {
-            let angle_rad = angle * std::f64::consts::PI / 180.;
-            let rise_rad = rise * std::f64::consts::PI / 180.;
-            let x = angle_rad.sin();
-            let z = angle_rad.cos();
-            let y = rise_rad.sin();
-            Vec3(x, y, z).unit() * dist + target
-        };
-        let w = (eye - target).unit();
-        let vert = {
-            let tilt_rad = tilt * std::f64::consts::PI / 180.;
-            let up = Vec3(0.0, 1.0, 0.0);
-            let horiz = up.cross(w);
-            up * tilt_rad.cos() + horiz * tilt_rad.sin()
+        let (lo, hi) = (bounds.min, bounds.max);
+        let corners = (0..8).map(|k| {
+            let pick = |bit, a: f64, b: f64| if k & bit == 0 { a } else { b };
+            Vec3(
+                pick(1, lo.0, hi.0),
+                pick(2, lo.1, hi.1),
+                pick(4, lo.2, hi.2),
+            ) - center
+        });
+        let dist = match projection {
+            // Each corner is in view if it is inside the cone of the view seen from the eye
+            Projection::Perspective => corners
+                .map(|p| p.dot(w) + (p.dot(u).abs() / half_wth).max(p.dot(v).abs() / half_hgt))
+                .fold(0., f64::max),
+            // The view is as large as the perspective one at the distance of the target,
+            // and the eye must remain outside of the box
+            Projection::Orthographic => corners
+                .map(|p| {
+                    (p.dot(u).abs() / half_wth)
+                        .max(p.dot(v).abs() / half_hgt)
+                        .max(p.dot(w))
+                })
+                .fold(0., f64::max),
+            Projection::Fisheye | Projection::Equirectangular => {
+                let radius = (hi - lo).len() / 2.;
+                radius / probe.half_fov.min(std::f64::consts::PI / 2.).sin()
+            }
         };
-        let u = vert.cross(w);
-        let v = w.cross(u);
-        Camera {
-            orig: eye,
-            low_left: eye - u * half_wth - v * half_hgt - w,
-            horiz: u * 2. * half_wth,
-            vert: v * 2. * half_hgt,
+        dist.max(EPSILON)
+    }
+
+    /// Cameras for the left and right eyes of a stereo rig centered on this camera
+    ///
+    /// ipd: interpupillary distance
+    ///
+    /// convergence: distance at which the views of both eyes coincide
+    ///
+    /// Equirectangular cameras become omni-directional stereo panoramas, for which the
+    /// convergence does not apply.
+    pub fn stereo(&self, ipd: f64, convergence: f64, mode: Convergence) -> (Self, Self) {
+        (
+            self.eye(-ipd / 2., convergence, mode),
+            self.eye(ipd / 2., convergence, mode),
+        )
+    }
+
+    /// Same camera moved sideways by `shift`
+    fn eye(&self, shift: f64, convergence: f64, mode: Convergence) -> Self {
+        if self.projection == Projection::Equirectangular {
+            return Self {
+                ods: shift,
+                ..self.clone()
+            };
+        }
+        let (x, y) = (self.horiz.unit(), self.vert.unit());
+        let orig = self.orig + x * shift;
+        match mode {
+            Convergence::OffAxis => Self {
+                orig,
+                low_left: self.low_left + x * (shift - shift / convergence),
+                ..self.clone()
+            },
+            Convergence::ToeIn => {
+                let target = self.orig + y.cross(x) * convergence;
+                let w = (orig - target).unit();
+                let u = y.cross(w).unit();
+                let v = w.cross(u);
+                let horiz = u * self.horiz.len();
+                let vert = v * self.vert.len();
+                Self {
+                    orig,
+                    low_left: orig - w - (horiz + vert) / 2.,
+                    horiz,
+                    vert,
+                    ..self.clone()
+                }
+            }
+        }
+    }
+
+    /// Replace the pinhole by a thin lens
+    ///
+    /// radius: radius of the lens, bigger values give a shallower depth of field
+    ///
+    /// focus: distance at which objects are sharp, `None` to focus on the target
+    pub fn set_lens(&mut self, radius: f64, focus: Option<f64>) {
+        self.lens = radius;
+        self.focus = focus.unwrap_or(self.target_dist);
+        self.autofocus = focus.is_none();
+    }
+
+    /// Distance given to `set_lens`, `None` when focusing on the target
+    pub fn focus(&self) -> Option<f64> {
+        if self.autofocus {
+            None
+        } else {
+            Some(self.focus)
         }
     }
 
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Keep the shutter open between two instants, the rays are cast at random times
+    /// in this interval so that moving objects are blurred
+    pub fn set_shutter(&mut self, open: f64, close: f64) {
+        self.shutter = (open, close);
+    }
+
+    /// Move the camera over time, the motion is applied to the whole camera in the
+    /// coordinates of the scene
+    pub fn set_motion(&mut self, motion: Motion) {
+        self.motion = Some(motion);
+    }
+
     /// Calculate the direction of a ray given by the position on the image of its destination
     pub fn get_ray(&self, u: f64, v: f64) -> Ray {
-        Ray {
-            orig: self.orig,
-            dir: self.low_left + self.horiz * u + self.vert * v - self.orig,
+        let mut r = self.cast(u, v);
+        if self.shutter.1 > self.shutter.0 {
+            r.time = self.shutter.0 + (self.shutter.1 - self.shutter.0) * rand::random::<f64>();
+        } else {
+            r.time = self.shutter.0;
+        }
+        if let Some(motion) = &self.motion {
+            let t = motion.at(r.time);
+            r.orig = t.apply_point(r.orig);
+            r.dir = t.apply_vector(r.dir);
+        }
+        r
+    }
+
+    /// Ray of the camera at rest
+    ///
+    /// With a lens the ray starts from a random point of the lens and goes through the
+    /// point of the focus plane that a pinhole camera would aim at. The lens is only
+    /// simulated for the perspective projection.
+    fn cast(&self, u: f64, v: f64) -> Ray {
+        let dir = self.low_left + self.horiz * u + self.vert * v - self.orig;
+        let (x, y) = (self.horiz.unit(), self.vert.unit());
+        // Direction of the center of the image
+        let front = y.cross(x);
+        match self.projection {
+            Projection::Perspective => (),
+            Projection::Orthographic => {
+                return Ray::new(self.orig + (dir - front) * self.target_dist, front)
+            }
+            Projection::Fisheye => {
+                let ratio = self.horiz.len() / self.vert.len();
+                let (px, py) = ((2. * u - 1.) * ratio, 2. * v - 1.);
+                let r = px.hypot(py);
+                let theta = r * self.half_fov;
+                let side = if r > 0. { (x * px + y * py) / r } else { x };
+                return Ray::new(self.orig, front * theta.cos() + side * theta.sin());
+            }
+            Projection::Equirectangular => {
+                let long = (u - 0.5) * 2. * PI;
+                let lat = (v - 0.5) * PI;
+                let tangent = x * long.cos() - front * long.sin();
+                return Ray::new(
+                    self.orig + tangent * self.ods,
+                    (front * long.cos() + x * long.sin()) * lat.cos() + y * lat.sin(),
+                );
+            }
+        }
+        if self.lens <= 0. {
+            return Ray::new(self.orig, dir);
+        }
+        let r = self.lens * rand::random::<f64>().sqrt();
+        let theta = 2. * PI * rand::random::<f64>();
+        let offset = x * (r * theta.cos()) + y * (r * theta.sin());
+        Ray::new(self.orig + offset, dir - offset / self.focus)
+    }
+}
+
+/// Description of a real camera in the units used by photographers, from which the
+/// field of view, the depth of field and the exposure are derived
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalCamera {
+    /// Focal length (mm)
+    pub focal: f64,
+    /// Width and height of the sensor (mm)
+    pub sensor: (f64, f64),
+    /// Focal length divided by the diameter of the aperture
+    pub f_number: f64,
+    /// Sensitivity of the sensor
+    pub iso: f64,
+    /// Exposure time (s)
+    pub shutter: f64,
+    /// Distance at which objects are sharp, `None` to focus on the target
+    pub focus: Option<f64>,
+    /// Length of a meter in the units of the scene
+    pub unit: f64,
+}
+
+impl PhysicalCamera {
+    /// Full frame (36x24mm) camera set for the "sunny 16" rule: f/16, ISO 100 and 1/100s,
+    /// which leaves the colors of the scene unchanged
+    pub fn full_frame(focal: f64) -> Self {
+        Self {
+            focal,
+            sensor: (36., 24.),
+            f_number: 16.,
+            iso: 100.,
+            shutter: 0.01,
+            focus: None,
+            unit: 1.,
+        }
+    }
+
+    /// Vertical field of view (degrees)
+    pub fn vfov(&self) -> f64 {
+        2. * (self.sensor.1 / 2. / self.focal).atan() * 180. / std::f64::consts::PI
+    }
+
+    /// Width/height aspect ratio of the sensor
+    pub fn aspect(&self) -> f64 {
+        self.sensor.0 / self.sensor.1
+    }
+
+    /// Radius of the aperture in the units of the scene
+    pub fn lens_radius(&self) -> f64 {
+        self.focal / self.f_number / 2. / 1000. * self.unit
+    }
+
+    /// Factor applied to the light received by the sensor, relative to the "sunny 16" settings
+    pub fn exposure(&self) -> f64 {
+        self.iso / 100. * self.shutter / 0.01 * 256. / (self.f_number * self.f_number)
+    }
+
+    /// Camera at eye aiming at target, with the sensor's field of view and aspect ratio
+    pub fn camera(&self, eye: Vec3, target: Vec3, vert: Vec3, projection: Projection) -> Camera {
+        let mut cam =
+            Camera::new_absolute(eye, target, vert, self.vfov(), self.aspect(), projection);
+        cam.set_lens(self.lens_radius(), self.focus);
+        cam.set_shutter(0., self.shutter);
+        cam
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_fisheye_keeps_orientation() {
+        let (right, up) = (Vec3(1., 0., 0.), Vec3(0., 1., 0.));
+        for &vfov in &[90., 180., 200., 300.] {
+            let cam = Camera::new_absolute(
+                Vec3(0., 0., 0.),
+                Vec3(0., 0., -1.),
+                up,
+                vfov,
+                1.5,
+                Projection::Fisheye,
+            );
+            let angle = |d: Vec3| d.unit().dot(Vec3(0., 0., -1.)).acos().to_degrees();
+            let r = cam.cast(0.6, 0.5);
+            assert!(r.dir.dot(right) > 0., "vfov = {}", vfov);
+            assert!(r.dir.dot(up).abs() < EPSILON);
+            let r = cam.cast(0.5, 0.6);
+            assert!(r.dir.dot(up) > 0., "vfov = {}", vfov);
+            assert!(r.dir.dot(right).abs() < EPSILON);
+            // The top edge is at half the field of view from the center
+            let r = cam.cast(0.5, 1.);
+            assert!((angle(r.dir) - vfov.min(360.) / 2.).abs() < 1e-6);
         }
     }
 }
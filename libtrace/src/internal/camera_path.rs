@@ -0,0 +1,71 @@
+use crate::internal::*;
+
+/// What the camera looks at while it follows its path
+#[derive(Clone)]
+pub enum Aim {
+    /// A fixed point
+    Point(Vec3),
+    /// A point that moves along its own path, at the same fraction of its length
+    Path(Path),
+    /// The point of the path of the camera that is this distance ahead
+    Ahead(f64),
+}
+
+/// Camera that travels along a curve at constant speed
+#[derive(Clone)]
+pub struct CameraPath {
+    pub path: Path,
+    pub aim: Aim,
+    /// Roll into the turns like an aircraft, the roll angle is `atan(bank * curvature)`
+    /// where the curvature is the one of the path seen from above. 0 keeps the horizon level.
+    pub bank: f64,
+}
+
+impl CameraPath {
+    pub fn new(path: Path, aim: Aim) -> Self {
+        Self {
+            path,
+            aim,
+            bank: 0.,
+        }
+    }
+
+    /// Position of the camera, point aimed at and roll (degrees) at fraction `s`
+    /// (between 0 and 1) of the length of the path
+    pub fn view(&self, s: f64) -> (Vec3, Vec3, f64) {
+        let eye = self.path.at(s);
+        let len = self.path.length();
+        let target = match &self.aim {
+            Aim::Point(p) => *p,
+            Aim::Path(p) => p.at(s),
+            Aim::Ahead(dist) => {
+                let ahead = s + dist / len;
+                if ahead <= 1. {
+                    self.path.at(ahead)
+                } else {
+                    // Keep looking straight after the end of the path
+                    self.path.at(1.) + self.path.tangent(1.).unit() * ((ahead - 1.) * len)
+                }
+            }
+        };
+        let roll = if self.bank == 0. {
+            0.
+        } else {
+            let h = 1e-3;
+            let (s1, s2) = ((s - h).max(0.), (s + h).min(1.));
+            let t1 = self.path.tangent(s1).unit();
+            let t2 = self.path.tangent(s2).unit();
+            // Positive when turning left, i.e. counterclockwise seen from above
+            let curvature = t1.cross(t2).1 / ((s2 - s1) * len);
+            -(self.bank * curvature).atan() * 180. / std::f64::consts::PI
+        };
+        (eye, target, roll)
+    }
+
+    /// Camera at fraction `s` of the length of the path
+    pub fn camera(&self, s: f64, vfov: f64, ratio: f64, projection: Projection) -> Camera {
+        let (eye, target, roll) = self.view(s);
+        let up = Quat::axis_angle(target - eye, roll).rotate(Vec3(0., 1., 0.));
+        Camera::new_absolute(eye, target, up, vfov, ratio, projection)
+    }
+}
@@ -0,0 +1,457 @@
+use crate::internal::*;
+use std::ops;
+use std::sync::Arc;
+
+/// Above this number of groups the canonical form costs more than it saves, and the
+/// tree is evaluated directly instead
+const MAX_GROUPS: usize = 32;
+
+/// Arbitrary combination of objects by set operations
+///
+/// Trees are usually built with the `&` (intersection), `|` (union) and `-` (difference)
+/// operators on primitives, then turned into a `Composite` by `build`.
+#[derive(Clone)]
+pub enum Csg {
+    Item(Primitive),
+    Union(Box<Csg>, Box<Csg>),
+    Inter(Box<Csg>, Box<Csg>),
+    Diff(Box<Csg>, Box<Csg>),
+}
+
+impl From<Primitive> for Csg {
+    fn from(p: Primitive) -> Self {
+        Csg::Item(p)
+    }
+}
+
+fn same(a: &Primitive, b: &Primitive) -> bool {
+    Arc::ptr_eq(&a.0, &b.0)
+}
+
+impl Csg {
+    /// Objects ready to be added to the scene: the canonical form if it is small enough,
+    /// otherwise a single object that evaluates the whole tree
+    pub fn build(self) -> Composite {
+        match self.expand(MAX_GROUPS) {
+            Some(groups) => groups,
+            None => vec![self.object().wrap()],
+        }
+    }
+
+    /// Whole tree as a single object, which does not need any rewriting
+    pub fn object(self) -> Primitive {
+        let mut leaves = Vec::new();
+        self.leaves(&mut leaves);
+        Primitive(Arc::new(CsgObject { tree: self, leaves }))
+    }
+
+    /// Rewrite the tree as a union of `Interaction`s, see the explanations below
+    pub fn canonical(&self) -> Composite {
+        self.expand(usize::MAX).unwrap()
+    }
+
+    /// Canonical form, or `None` as soon as it would need more than `limit` groups
+    fn expand(&self, limit: usize) -> Option<Composite> {
+        let groups = match self {
+            Csg::Item(p) => vec![p.clone().wrap()],
+            Csg::Union(a, b) => {
+                let mut res = a.expand(limit)?;
+                res.extend(b.expand(limit)?);
+                res
+            }
+            Csg::Inter(a, b) => {
+                let a_can = a.expand(limit)?;
+                let b_can = b.expand(limit)?;
+                if a_can.len() * b_can.len() > limit {
+                    return None;
+                }
+                let mut res = Vec::new();
+                for x in &a_can {
+                    for y in &b_can {
+                        let mut inside = x.0.clone();
+                        inside.extend(y.0.iter().cloned());
+                        let mut outside = x.1.clone();
+                        outside.extend(y.1.iter().cloned());
+                        res.push(Interaction(inside, outside));
+                    }
+                }
+                res
+            }
+            Csg::Diff(a, b) => {
+                let mut res = a.expand(limit)?;
+                for y in b.expand(limit)? {
+                    let mut next = Vec::new();
+                    for x in &res {
+                        for z in &y.0 {
+                            next.push(x.clone().remove(z.clone()));
+                        }
+                        for d in &y.1 {
+                            next.push(x.clone().intersect(d.clone()));
+                        }
+                    }
+                    res = absorb(next.into_iter().filter_map(simplify).collect());
+                    if res.len() > limit {
+                        return None;
+                    }
+                }
+                res
+            }
+        };
+        let groups = absorb(groups.into_iter().filter_map(simplify).collect());
+        if groups.len() > limit {
+            None
+        } else {
+            Some(groups)
+        }
+    }
+
+    pub fn contains(&self, pos: Vec3) -> bool {
+        match self {
+            Csg::Item(p) => p.inside(pos),
+            Csg::Union(a, b) => a.contains(pos) || b.contains(pos),
+            Csg::Inter(a, b) => a.contains(pos) && b.contains(pos),
+            Csg::Diff(a, b) => a.contains(pos) && !b.contains(pos),
+        }
+    }
+
+    /// Same as `contains`, except that `leaf` is assumed to contain (or not) the point
+    fn contains_assuming(&self, pos: Vec3, leaf: &Primitive, value: bool) -> bool {
+        match self {
+            Csg::Item(p) if same(p, leaf) => value,
+            Csg::Item(p) => p.inside(pos),
+            Csg::Union(a, b) => {
+                a.contains_assuming(pos, leaf, value) || b.contains_assuming(pos, leaf, value)
+            }
+            Csg::Inter(a, b) => {
+                a.contains_assuming(pos, leaf, value) && b.contains_assuming(pos, leaf, value)
+            }
+            Csg::Diff(a, b) => {
+                a.contains_assuming(pos, leaf, value) && !b.contains_assuming(pos, leaf, value)
+            }
+        }
+    }
+
+    /// Portions of the line of the ray inside of the tree, if all leaves provide theirs
+    pub fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        Some(match self {
+            Csg::Item(p) => p.intervals(r)?,
+            Csg::Union(a, b) => Span::union(&a.intervals(r)?, &b.intervals(r)?),
+            Csg::Inter(a, b) => Span::intersection(&a.intervals(r)?, &b.intervals(r)?),
+            Csg::Diff(a, b) => Span::difference(&a.intervals(r)?, &b.intervals(r)?),
+        })
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        match self {
+            Csg::Item(p) => p.bounds(),
+            Csg::Union(a, b) => a.bounds().zip(b.bounds()).map(|(a, b)| a.union(b)),
+            Csg::Inter(a, b) => match (a.bounds(), b.bounds()) {
+                (Some(a), Some(b)) => Some(a.intersection(b)),
+                (a, b) => a.or(b),
+            },
+            Csg::Diff(a, _) => a.bounds(),
+        }
+    }
+
+    /// All distinct primitives of the tree
+    fn leaves(&self, acc: &mut Vec<Primitive>) {
+        match self {
+            Csg::Item(p) => {
+                if !acc.iter().any(|q| same(p, q)) {
+                    acc.push(p.clone());
+                }
+            }
+            Csg::Union(a, b) | Csg::Inter(a, b) | Csg::Diff(a, b) => {
+                a.leaves(acc);
+                b.leaves(acc);
+            }
+        }
+    }
+}
+
+/// Remove duplicate and useless items of a group, or the whole group if it is
+/// obviously empty
+fn simplify(group: Interaction) -> Option<Interaction> {
+    let Interaction(inside, outside) = group;
+    let mut res = Interaction(Vec::new(), Vec::new());
+    for p in inside {
+        if !res.0.iter().any(|q| same(&p, q)) {
+            res.0.push(p);
+        }
+    }
+    let bounds = res.bounds();
+    if let Some(true) = bounds.map(|b| b.is_empty()) {
+        return None;
+    }
+    for p in outside {
+        if res.0.iter().any(|q| same(&p, q)) {
+            // Inside and outside of the same object
+            return None;
+        }
+        // Boxes also contain the inside (see `Hit::bounds`), so an outside item whose
+        // box misses the group cannot remove any of its points
+        let far = match (bounds, p.bounds()) {
+            (Some(a), Some(b)) => a.intersection(b).is_empty(),
+            _ => false,
+        };
+        if !far && !res.1.iter().any(|q| same(&p, q)) {
+            res.1.push(p);
+        }
+    }
+    Some(res)
+}
+
+/// Remove the groups that are included in another one, i.e. that have more
+/// restrictions of both kinds
+fn absorb(groups: Vec<Interaction>) -> Vec<Interaction> {
+    let subset = |a: &[Primitive], b: &[Primitive]| a.iter().all(|p| b.iter().any(|q| same(p, q)));
+    let wider = |a: &Interaction, b: &Interaction| subset(&a.0, &b.0) && subset(&a.1, &b.1);
+    let mut res: Vec<Interaction> = Vec::new();
+    for g in groups {
+        if res.iter().any(|h| wider(h, &g)) {
+            continue;
+        }
+        res.retain(|h| !wider(&g, h));
+        res.push(g);
+    }
+    res
+}
+
+// About the canonical form
+//
+// The rendering loop only manages objects expressed as :
+// Union_i ( Inter_j (A_i,j) \ Union_k (B_i,k) )
+// This corresponds to a collection (i.e. union) of Interaction(Vec, Vec),
+// where the first (resp. second) Vec represents all objects inside
+// (resp. outside) of which we need to be.
+//
+// The translation is done recursively by `Csg::expand()`.
+// It relies on the following :
+// (&, |, \ represent intersection, union, difference)
+// * a leaf is already under canonical representation :
+//        A -> { [A, ()] }
+// * an union is easy to canonicalize
+//        X | Y -> { X, Y }
+// * A\B & C\D = A&C \ B|D
+//        [A, B] & [C, D] -> [(A.., C..), (B.., D..)]
+// * & is distributive on |
+//        { [A, B], [A', B'] } & { [C, D], [C', D'] }
+//     -> {
+//             [(A.., C..), (B.., D..)],
+//             [(A'.., C..), (B'.., D..)],
+//             [(A.., C'..), (B.., D'..)],
+//             [(A'.., C'..), (B'.., D'..)],
+//        }
+// And finally:
+// * { X, Y } \ { Z, W } = { X\Z\W, Y\Z\W }
+// * [A, B] \ [(C, C'), ()] = { [A, (B.., C)], [A, (B.., C')] }
+// * [A, B] \ [C, (D, D')] = { [A, (B.., C)], [(A.., D), B], [(A.., D'), B] }
+// These rules are applied by iteratively removing all elements
+// of the canonical form of the right side at each step.
+//
+// Differences multiply the number of groups, so groups that are obviously empty
+// are dropped along the way, and the tree is kept whole when the expansion
+// grows too big anyway.
+
+/// Whole tree evaluated as a single object
+pub struct CsgObject {
+    tree: Csg,
+    leaves: Vec<Primitive>,
+}
+
+impl Hit for CsgObject {
+    /// Exact when all leaves provide their intervals, otherwise every hit on a leaf
+    /// is kept if the tree changes from inside to outside at that point
+    fn hit(&self, r: &Ray) -> HitRecord {
+        if let Some(spans) = self.tree.intervals(r) {
+            return Span::first_hit(&spans, r);
+        }
+        let mut record = HitRecord::Blank;
+        for leaf in &self.leaves {
+            let mut ray = *r;
+            let mut offset = 0.0;
+            while let HitRecord::Hit(h) = leaf.hit(&ray) {
+                if self.tree.contains_assuming(h.pos, leaf, true)
+                    != self.tree.contains_assuming(h.pos, leaf, false)
+                {
+                    record.compare(HitRecord::Hit(h.later(offset)));
+                }
+                ray.orig = h.pos + ray.dir * EPSILON;
+                offset += h.t;
+            }
+        }
+        record
+    }
+
+    fn texture(&self) -> Texture {
+        self.leaves[0].texture()
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.tree.contains(pos)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.tree.bounds()
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        self.tree.intervals(r)
+    }
+}
+
+macro_rules! csg_operator {
+    ( $tr:ident, $f:ident, $variant:ident ) => {
+        impl<T: Into<Csg>> ops::$tr<T> for Csg {
+            type Output = Csg;
+
+            fn $f(self, other: T) -> Csg {
+                Csg::$variant(Box::new(self), Box::new(other.into()))
+            }
+        }
+
+        impl<T: Into<Csg>> ops::$tr<T> for Primitive {
+            type Output = Csg;
+
+            fn $f(self, other: T) -> Csg {
+                Csg::$variant(Box::new(self.into()), Box::new(other.into()))
+            }
+        }
+    };
+}
+
+csg_operator!(BitOr, bitor, Union);
+csg_operator!(BitAnd, bitand, Inter);
+csg_operator!(Sub, sub, Diff);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_vec(rng: &mut StdRng, size: f64) -> Vec3 {
+        Vec3(
+            rng.gen_range(-size, size),
+            rng.gen_range(-size, size),
+            rng.gen_range(-size, size),
+        )
+    }
+
+    /// Overlapping spheres around the origin
+    fn spheres(rng: &mut StdRng, n: usize) -> Vec<Primitive> {
+        (0..n)
+            .map(|_| {
+                Sphere {
+                    center: random_vec(rng, 0.7),
+                    radius: rng.gen_range(0.5, 1.),
+                    texture: Texture::Lambertian(RGB(0.5, 0.5, 0.5)),
+                }
+                .build()
+            })
+            .collect()
+    }
+
+    fn first_hit(groups: &[Interaction], r: &Ray) -> Option<f64> {
+        let mut rec = HitRecord::Blank;
+        for g in groups {
+            rec.compare(g.hit(r));
+        }
+        match rec {
+            HitRecord::Hit(h) => Some(h.t),
+            HitRecord::Blank => None,
+        }
+    }
+
+    /// Compare `groups` with the direct evaluation of `tree`, both on points and on rays
+    fn check_agree(tree: &Csg, groups: &[Interaction], seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let whole = tree.clone().object();
+        let (mut inside, mut hits) = (0, 0);
+        for _ in 0..2000 {
+            let pos = random_vec(&mut rng, 2.);
+            let expected = tree.contains(pos);
+            assert_eq!(groups.iter().any(|g| g.contains(pos)), expected);
+            inside += expected as usize;
+            // From outside of all groups, where the surface of their union is the
+            // first one met
+            let orig = random_vec(&mut rng, 1.).unit() * 5.;
+            let ray = Ray::new(orig, pos - orig);
+            let (a, b) = (first_hit(groups, &ray), whole.hit(&ray));
+            match (a, b) {
+                (None, HitRecord::Blank) => (),
+                (Some(t), HitRecord::Hit(h)) => {
+                    assert!((t - h.t).abs() < 1e-6);
+                    hits += 1;
+                }
+                _ => panic!("Hit by only one of the two forms"),
+            }
+        }
+        assert!(inside > 0 && hits > 0);
+    }
+
+    #[test]
+    fn nested_differences() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for seed in 0..10 {
+            let s = spheres(&mut rng, 4);
+            let tree = s[0].clone() - (s[1].clone() - (s[2].clone() - s[3].clone()));
+            check_agree(&tree, &tree.clone().build(), seed);
+        }
+    }
+
+    #[test]
+    fn intersections_of_unions() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for seed in 0..10 {
+            let s = spheres(&mut rng, 6);
+            let tree = ((s[0].clone() | s[1].clone()) & (s[2].clone() | s[3].clone()))
+                - (s[4].clone() & s[5].clone());
+            check_agree(&tree, &tree.clone().build(), seed);
+        }
+    }
+
+    #[test]
+    fn too_many_groups() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let s = spheres(&mut rng, 12);
+        let union = |items: &[Primitive]| {
+            items[1..]
+                .iter()
+                .fold(Csg::from(items[0].clone()), |acc, p| acc | p.clone())
+        };
+        let tree = union(&s[..6]) & union(&s[6..]);
+        let canonical = tree.canonical();
+        assert!(canonical.len() > MAX_GROUPS);
+        let built = tree.clone().build();
+        assert_eq!(built.len(), 1);
+        check_agree(&tree, &built, 3);
+        check_agree(&tree, &canonical, 4);
+    }
+
+    #[test]
+    fn diff_with_terrain_above() {
+        let texture = Texture::Lambertian(RGB(0.5, 0.5, 0.5));
+        let sphere = Sphere {
+            center: Vec3(0., -5., 0.),
+            radius: 1.,
+            texture,
+        }
+        .build();
+        let terrain = HeightField {
+            orig: Vec3(-10., 0., -10.),
+            wth: 20.,
+            dep: 20.,
+            hgt: 1.,
+            grid: vec![vec![0., 1.], vec![1., 0.]],
+            texture,
+        }
+        .build();
+        let groups = (sphere - terrain).canonical();
+        assert!(!groups.iter().any(|g| g.contains(Vec3(0., -5., 0.))));
+        let ray = Ray::new(Vec3(0., -5., -20.), Vec3(0., 0., 1.));
+        assert!(groups
+            .iter()
+            .all(|g| matches!(g.hit(&ray), HitRecord::Blank)));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Prism obtained by sliding a flat polygon along a vector
+///
+/// The polygon is described in the plane spanned by `u` and `v`: the point `(x, y)`
+/// is at `orig + u * x + v * y`.
+/// It may have holes, the inside is determined by the even-odd rule so the orientation
+/// of each loop does not matter.
+#[derive(Clone)]
+pub struct Extrusion {
+    /// Origin of the coordinates of the polygon, on the bottom face
+    pub orig: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    /// Translation from the bottom face to the top face
+    pub w: Vec3,
+    /// Outer boundary, the last point is implicitly joined to the first one
+    pub outline: Vec<(f64, f64)>,
+    pub holes: Vec<Vec<(f64, f64)>>,
+    pub texture: Texture,
+}
+
+/// Twice the signed area of a closed polygon
+fn area(pts: &[(f64, f64)]) -> f64 {
+    let n = pts.len();
+    (0..n).fold(0., |acc, i| {
+        let (a, b) = (pts[i], pts[(i + 1) % n]);
+        acc + a.0 * b.1 - b.0 * a.1
+    })
+}
+
+impl Extrusion {
+    pub fn build(self) -> Primitive {
+        let n = self.u.cross(self.v);
+        let det = n.dot(self.w);
+        // Flip the caps if `w` goes to the other side of the polygon
+        let up = if det > 0. { n } else { -n };
+        let mut edges = Vec::new();
+        let mut loops = vec![self.outline.clone()];
+        loops.extend(self.holes.iter().cloned());
+        for (k, pts) in loops.iter().enumerate() {
+            // Direction of the outside relative to the direction of the edges
+            let sign = area(pts).signum() * if k == 0 { 1. } else { -1. };
+            for i in 0..pts.len() {
+                let (a, b) = (pts[i], pts[(i + 1) % pts.len()]);
+                let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+                let out = self.u * (dy * sign) - self.v * (dx * sign);
+                let normal = (self.u * dx + self.v * dy).cross(self.w);
+                edges.push(Edge {
+                    a,
+                    b,
+                    normal: if normal.dot(out) > 0. {
+                        normal
+                    } else {
+                        -normal
+                    },
+                });
+            }
+        }
+        let mut corners = Vec::new();
+        for &(x, y) in &self.outline {
+            let p = self.orig + self.u * x + self.v * y;
+            corners.push(p);
+            corners.push(p + self.w);
+        }
+        Primitive(Arc::new(ExtrusionObject {
+            orig: self.orig,
+            // Dual basis, to get the coordinates of a point
+            du: self.v.cross(self.w) / det,
+            dv: self.w.cross(self.u) / det,
+            dw: n / det,
+            up,
+            edges,
+            bounds: Aabb::around(&corners).pad(EPSILON),
+            texture: self.texture,
+        }))
+    }
+}
+
+struct Edge {
+    a: (f64, f64),
+    b: (f64, f64),
+    /// Outward normal of the side face
+    normal: Vec3,
+}
+
+pub struct ExtrusionObject {
+    orig: Vec3,
+    du: Vec3,
+    dv: Vec3,
+    dw: Vec3,
+    /// Outward normal of the top face
+    up: Vec3,
+    /// Edges of all loops of the polygon
+    edges: Vec<Edge>,
+    bounds: Aabb,
+    texture: Texture,
+}
+
+impl ExtrusionObject {
+    /// Coordinates in the basis `(u, v, w)`
+    fn local(&self, p: Vec3) -> Vec3 {
+        Vec3(p.dot(self.du), p.dot(self.dv), p.dot(self.dw))
+    }
+
+    /// Even-odd test in the plane of the polygon
+    fn in_polygon(&self, x: f64, y: f64) -> bool {
+        let mut inside = false;
+        for e in &self.edges {
+            let (a, b) = (e.a, e.b);
+            if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
+
+impl Hit for ExtrusionObject {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        if self.bounds.range(r).is_none() {
+            return HitRecord::Blank;
+        }
+        let o = self.local(r.orig - self.orig);
+        let d = self.local(r.dir);
+        let mut rec = HitRecord::Blank;
+        // Caps
+        if d.2.abs() > EPSILON * EPSILON {
+            for &(z, normal) in &[(0., -self.up), (1., self.up)] {
+                let t = (z - o.2) / d.2;
+                if t > EPSILON && self.in_polygon(o.0 + d.0 * t, o.1 + d.1 * t) {
+                    rec.compare(HitRecord::make(t, r.project(t), normal, self.texture));
+                }
+            }
+        }
+        // Sides
+        for e in &self.edges {
+            let (ex, ey) = (e.b.0 - e.a.0, e.b.1 - e.a.1);
+            let den = d.0 * ey - d.1 * ex;
+            if den.abs() < EPSILON * EPSILON {
+                continue;
+            }
+            let (ax, ay) = (e.a.0 - o.0, e.a.1 - o.1);
+            let t = (ax * ey - ay * ex) / den;
+            let s = (ax * d.1 - ay * d.0) / den;
+            let z = o.2 + d.2 * t;
+            if t > EPSILON && (0.0..=1.).contains(&s) && (0.0..=1.).contains(&z) {
+                rec.compare(HitRecord::make(t, r.project(t), e.normal, self.texture));
+            }
+        }
+        rec
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        let p = self.local(pos - self.orig);
+        0. < p.2 && p.2 < 1. && self.in_polygon(p.0, p.1)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
@@ -0,0 +1,400 @@
+use crate::internal::*;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+
+/// Terrain described by the altitude of each point of a regular grid
+///
+/// Each cell of the grid is split into two triangles, shading uses normals interpolated
+/// between the vertices.
+/// The inside of the object is everything that is below the surface, within the extent
+/// of the grid: there is no bottom and there are no sides.
+#[derive(Clone)]
+pub struct HeightField {
+    /// Corner of the grid with the lowest x and z, at altitude 0
+    pub orig: Vec3,
+    /// Size along x
+    pub wth: f64,
+    /// Size along z
+    pub dep: f64,
+    /// Altitude of the points that have a value of 1 in the grid
+    pub hgt: f64,
+    /// `grid[i][j]` is the value on the i-th row (along z) and j-th column (along x),
+    /// at least 2x2
+    pub grid: Vec<Vec<f64>>,
+    pub texture: Texture,
+}
+
+/// Read the next whitespace-delimited word of a Netpbm header, skipping comments
+fn next_word(data: &[u8], pos: &mut usize) -> String {
+    loop {
+        while *pos < data.len() && data[*pos].is_ascii_whitespace() {
+            *pos += 1;
+        }
+        if *pos < data.len() && data[*pos] == b'#' {
+            while *pos < data.len() && data[*pos] != b'\n' {
+                *pos += 1;
+            }
+        } else {
+            break;
+        }
+    }
+    let start = *pos;
+    while *pos < data.len() && !data[*pos].is_ascii_whitespace() {
+        *pos += 1;
+    }
+    String::from_utf8_lossy(&data[start..*pos]).to_string()
+}
+
+impl HeightField {
+    /// Load altitudes from a Netpbm image (`P2`, `P3`, `P5` or `P6`).
+    ///
+    /// White is at altitude `hgt`, black at altitude 0.
+    /// Colors are averaged, and the top of the image is at the lowest z.
+    /// Fails with `InvalidData` on a malformed or truncated image.
+    pub fn load(
+        file: &str,
+        orig: Vec3,
+        wth: f64,
+        dep: f64,
+        hgt: f64,
+        texture: Texture,
+    ) -> io::Result<Self> {
+        let invalid =
+            |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", file, msg));
+        let data = fs::read(file)?;
+        let mut pos = 0;
+        let magic = next_word(&data, &mut pos);
+        let channels = match &magic[..] {
+            "P2" | "P5" => 1,
+            "P3" | "P6" => 3,
+            _ => return Err(invalid("not a P2, P3, P5 or P6 image")),
+        };
+        let mut get = || {
+            next_word(&data, &mut pos)
+                .parse::<usize>()
+                .map_err(|_| invalid("malformed header"))
+        };
+        let cols = get()?;
+        let rows = get()?;
+        let max = get()?;
+        if max == 0 || max > 65535 {
+            return Err(invalid("maximum value out of range"));
+        }
+        if rows < 2 || cols < 2 {
+            return Err(invalid("image smaller than 2x2"));
+        }
+        let max = max as f64;
+        let count = cols
+            .checked_mul(channels)
+            .and_then(|n| n.checked_mul(rows))
+            .ok_or_else(|| invalid("image too large"))?;
+        let values = if magic == "P2" || magic == "P3" {
+            String::from_utf8_lossy(&data[pos..])
+                .split_whitespace()
+                .take(count)
+                .map(|x| x.parse::<f64>().map_err(|_| invalid("malformed value")))
+                .collect::<io::Result<Vec<_>>>()?
+        } else {
+            // Exactly one whitespace character between the header and the data
+            let raw = data.get(pos + 1..).unwrap_or(&[]);
+            if max < 256. {
+                raw.iter().take(count).map(|&x| f64::from(x)).collect()
+            } else {
+                raw.chunks_exact(2)
+                    .take(count)
+                    .map(|c| f64::from(c[0]) * 256. + f64::from(c[1]))
+                    .collect()
+            }
+        };
+        if values.len() < count {
+            return Err(invalid("truncated data"));
+        }
+        let grid = values
+            .chunks(cols * channels)
+            .map(|row| {
+                row.chunks(channels)
+                    .map(|px| px.iter().sum::<f64>() / channels as f64 / max)
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            orig,
+            wth,
+            dep,
+            hgt,
+            grid,
+            texture,
+        })
+    }
+
+    pub fn build(self) -> Primitive {
+        let rows = self.grid.len();
+        let cols = self.grid.first().map_or(0, |r| r.len());
+        if rows < 2 || cols < 2 || self.grid.iter().any(|r| r.len() != cols) {
+            panic!(
+                "A height field needs a grid of at least 2x2 values with rows of the same length"
+            );
+        }
+        let dx = self.wth / (cols - 1) as f64;
+        let dz = self.dep / (rows - 1) as f64;
+        let alt = |i: usize, j: usize| self.grid[i][j] * self.hgt;
+        let mut normals = Vec::new();
+        for i in 0..rows {
+            let mut row = Vec::new();
+            for j in 0..cols {
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(cols - 1));
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(rows - 1));
+                let sx = (alt(i, j1) - alt(i, j0)) / ((j1 - j0) as f64 * dx);
+                let sz = (alt(i1, j) - alt(i0, j)) / ((i1 - i0) as f64 * dz);
+                row.push(Vec3(-sx, 1., -sz).unit());
+            }
+            normals.push(row);
+        }
+        let (lo, hi) = self
+            .grid
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| {
+                (lo.min(x * self.hgt), hi.max(x * self.hgt))
+            });
+        let bounds = Aabb::around(&[
+            self.orig + Vec3(0., lo, 0.),
+            self.orig + Vec3(self.wth, hi, self.dep),
+        ])
+        .pad(EPSILON);
+        Primitive(Arc::new(HeightFieldObject {
+            orig: self.orig,
+            dx,
+            dz,
+            rows,
+            cols,
+            alt: self
+                .grid
+                .iter()
+                .map(|r| r.iter().map(|x| x * self.hgt).collect())
+                .collect(),
+            normals,
+            bounds,
+            texture: self.texture,
+        }))
+    }
+}
+
+pub struct HeightFieldObject {
+    orig: Vec3,
+    /// Size of a cell
+    dx: f64,
+    dz: f64,
+    rows: usize,
+    cols: usize,
+    /// Altitude of each vertex relative to `orig`
+    alt: Vec<Vec<f64>>,
+    normals: Vec<Vec<Vec3>>,
+    /// Box around the surface only, used to clip rays: the inside has no bottom
+    bounds: Aabb,
+    texture: Texture,
+}
+
+impl HeightFieldObject {
+    /// Cell that contains a point, and coordinates of the point inside the cell
+    fn locate(&self, p: Vec3) -> (usize, usize, f64, f64) {
+        let x = (p.0 - self.orig.0) / self.dx;
+        let z = (p.2 - self.orig.2) / self.dz;
+        let j = (x.floor().max(0.) as usize).min(self.cols - 2);
+        let i = (z.floor().max(0.) as usize).min(self.rows - 2);
+        (i, j, x - j as f64, z - i as f64)
+    }
+
+    /// Interpolated altitude of the surface (relative to `orig`)
+    fn altitude(&self, i: usize, j: usize, fx: f64, fz: f64) -> f64 {
+        let a = &self.alt;
+        if fx >= fz {
+            a[i][j] + (a[i][j + 1] - a[i][j]) * fx + (a[i + 1][j + 1] - a[i][j + 1]) * fz
+        } else {
+            a[i][j] + (a[i + 1][j + 1] - a[i + 1][j]) * fx + (a[i + 1][j] - a[i][j]) * fz
+        }
+    }
+
+    /// Interpolated normal, using the barycentric coordinates inside of the triangle
+    fn normal(&self, i: usize, j: usize, fx: f64, fz: f64) -> Vec3 {
+        let n = &self.normals;
+        if fx >= fz {
+            n[i][j] * (1. - fx) + n[i][j + 1] * (fx - fz) + n[i + 1][j + 1] * fz
+        } else {
+            n[i][j] * (1. - fz) + n[i + 1][j + 1] * fx + n[i + 1][j] * (fz - fx)
+        }
+    }
+
+    /// Intersection with the two triangles of a cell
+    fn hit_cell(&self, r: &Ray, i: usize, j: usize) -> HitRecord {
+        let a = &self.alt;
+        let x0 = self.orig.0 + j as f64 * self.dx;
+        let z0 = self.orig.2 + i as f64 * self.dz;
+        // Each triangle is a plane y = base + sx * (x - x0) + sz * (z - z0)
+        let planes = [
+            (
+                a[i][j],
+                (a[i][j + 1] - a[i][j]) / self.dx,
+                (a[i + 1][j + 1] - a[i][j + 1]) / self.dz,
+                true,
+            ),
+            (
+                a[i][j],
+                (a[i + 1][j + 1] - a[i + 1][j]) / self.dx,
+                (a[i + 1][j] - a[i][j]) / self.dz,
+                false,
+            ),
+        ];
+        let mut rec = HitRecord::Blank;
+        for &(base, sx, sz, upper) in &planes {
+            let den = r.dir.1 - sx * r.dir.0 - sz * r.dir.2;
+            if den.abs() < EPSILON * EPSILON {
+                continue;
+            }
+            let temp =
+                (self.orig.1 + base + sx * (r.orig.0 - x0) + sz * (r.orig.2 - z0) - r.orig.1) / den;
+            if temp <= EPSILON {
+                continue;
+            }
+            let pos = r.project(temp);
+            let fx = (pos.0 - x0) / self.dx;
+            let fz = (pos.2 - z0) / self.dz;
+            let e = EPSILON;
+            let in_cell = -e <= fx && fx <= 1. + e && -e <= fz && fz <= 1. + e;
+            let in_triangle = if upper { fx + e >= fz } else { fz + e >= fx };
+            if in_cell && in_triangle {
+                rec.compare(HitRecord::make(
+                    temp,
+                    pos,
+                    self.normal(i, j, fx.clamp(0., 1.), fz.clamp(0., 1.)),
+                    self.texture,
+                ));
+            }
+        }
+        rec
+    }
+}
+
+impl Hit for HeightFieldObject {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let (tmin, tmax) = match self.bounds.range(r) {
+            None => return HitRecord::Blank,
+            Some(range) => range,
+        };
+        if tmax < EPSILON {
+            return HitRecord::Blank;
+        }
+        let tmin = tmin.max(0.);
+        // Walk through the cells crossed by the projection of the ray on the grid
+        let (mut i, mut j, _, _) = self.locate(r.project(tmin));
+        let step = |d: f64| if d > 0. { 1 } else { -1 };
+        let (si, sj) = (step(r.dir.2), step(r.dir.0));
+        let next = |k: usize, s: isize, o: f64, d: f64, size: f64, orig: f64| {
+            if d.abs() < EPSILON {
+                f64::INFINITY
+            } else {
+                let bound = orig + (k as isize + if s > 0 { 1 } else { 0 }) as f64 * size;
+                (bound - o) / d
+            }
+        };
+        let mut ti = next(i, si, r.orig.2, r.dir.2, self.dz, self.orig.2);
+        let mut tj = next(j, sj, r.orig.0, r.dir.0, self.dx, self.orig.0);
+        let di = (self.dz / r.dir.2).abs();
+        let dj = (self.dx / r.dir.0).abs();
+        loop {
+            if let HitRecord::Hit(h) = self.hit_cell(r, i, j) {
+                return HitRecord::Hit(h);
+            }
+            if ti.min(tj) > tmax {
+                return HitRecord::Blank;
+            }
+            if ti < tj {
+                if (si < 0 && i == 0) || (si > 0 && i == self.rows - 2) {
+                    return HitRecord::Blank;
+                }
+                i = (i as isize + si) as usize;
+                ti += di;
+            } else {
+                if (sj < 0 && j == 0) || (sj > 0 && j == self.cols - 2) {
+                    return HitRecord::Blank;
+                }
+                j = (j as isize + sj) as usize;
+                tj += dj;
+            }
+        }
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        let x = pos.0 - self.orig.0;
+        let z = pos.2 - self.orig.2;
+        if x < 0.
+            || z < 0.
+            || x > self.dx * (self.cols - 1) as f64
+            || z > self.dz * (self.rows - 1) as f64
+        {
+            return false;
+        }
+        let (i, j, fx, fz) = self.locate(pos);
+        pos.1 - self.orig.1 < self.altitude(i, j, fx, fz)
+    }
+
+    // No `bounds`: the inside extends indefinitely below the surface
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_bytes(test: &str, data: &[u8]) -> io::Result<HeightField> {
+        let path = std::env::temp_dir().join(format!("hf-{}-{}.pgm", test, std::process::id()));
+        fs::write(&path, data).unwrap();
+        let texture = Texture::Lambertian(RGB(0.5, 0.5, 0.5));
+        let hf = HeightField::load(
+            path.to_str().unwrap(),
+            Vec3(0., 0., 0.),
+            1.,
+            1.,
+            1.,
+            texture,
+        );
+        fs::remove_file(&path).unwrap();
+        hf
+    }
+
+    #[test]
+    fn load_formats() {
+        let grid = vec![vec![0., 1.], vec![0.2, 0.]];
+        for (test, data) in &[
+            ("p2", &b"P2\n# comment\n2 2\n255\n0 255 51 0\n"[..]),
+            ("p3", b"P3 2 2 255 0 0 0 255 255 255 51 51 51 0 0 0"),
+            ("p5", b"P5\n2 2\n255\n\x00\xff\x33\x00"),
+            (
+                "p5-wide",
+                b"P5\n2 2\n65535\n\x00\x00\xff\xff\x33\x33\x00\x00",
+            ),
+        ] {
+            assert_eq!(load_bytes(test, data).unwrap().grid, grid);
+        }
+    }
+
+    #[test]
+    fn load_malformed() {
+        for (test, data) in &[
+            ("magic", &b"P7\n2 2\n255\n"[..]),
+            ("header", b"P2\n2 x\n255\n"),
+            ("max", b"P2\n2 2\n0\n0 0 0 0\n"),
+            ("small", b"P2\n1 2\n255\n0 0\n"),
+            ("value", b"P2\n2 2\n255\n0 0 z 0\n"),
+            ("truncated", b"P5\n2 2\n255\n\x00\xff"),
+            ("truncated-wide", b"P5\n2 2\n65535\n\x00\xff\x00"),
+        ] {
+            let err = load_bytes(test, data).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -7,6 +7,24 @@ pub trait Hit: Send + Sync {
     fn hit(&self, r: &Ray) -> HitRecord;
     fn texture(&self) -> Texture;
     fn inside(&self, pos: Vec3) -> bool;
+
+    /// Box that contains the whole surface and all points for which `inside` is true,
+    /// if the object is bounded
+    ///
+    /// Objects whose inside extends past their surface (e.g. everything below a
+    /// terrain) must return `None`: culling and CSG simplification rely on the box.
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+
+    /// All portions of the line of the ray that are inside of the object, sorted
+    ///
+    /// Closed objects that can compute them directly should implement this method:
+    /// set operations on these spans are exact, while the fallback has to test every
+    /// hit against all other objects of the interaction.
+    fn intervals(&self, _r: &Ray) -> Option<Vec<Span>> {
+        None
+    }
 }
 
 /// Records information on the surface with which the ray was calculated to intersect.
@@ -99,6 +117,14 @@ impl Primitive {
     pub fn inside(&self, pos: Vec3) -> bool {
         self.0.inside(pos)
     }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.0.bounds()
+    }
+
+    pub fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        self.0.intervals(r)
+    }
 }
 
 /// A single indivisible object that can be added to the scene without being decomposed.
@@ -116,8 +142,8 @@ impl Interaction {
     ///
     /// Some specific objects may provide a less costly way to make this test.
     pub fn bidir_hit<T: Hit>(obj: &T, pos: Vec3, v: Vec3) -> bool {
-        let ray1 = Ray { orig: pos, dir: v };
-        let ray2 = Ray { orig: pos, dir: -v };
+        let ray1 = Ray::new(pos, v);
+        let ray2 = Ray::new(pos, -v);
         match (obj.hit(&ray1), obj.hit(&ray2)) {
             (HitRecord::Blank, _) => false,
             (_, HitRecord::Blank) => false,
@@ -161,6 +187,97 @@ impl Interaction {
         self.1.push(other);
     }
 
+    /// Nearest point of the surface of the interaction, i.e. nearest point of the surface
+    /// of one of its items that is inside of all other items of the first vector and
+    /// outside of all items of the second vector
+    ///
+    /// When all items provide their intervals the set operations are done on them,
+    /// otherwise every hit is checked with the inside/outside tests.
+    pub fn hit(&self, r: &Ray) -> HitRecord {
+        if self.0.len() == 1 && self.1.is_empty() {
+            return self.0[0].hit(r);
+        }
+        match self.intervals(r) {
+            Some(spans) => Span::first_hit(&spans, r),
+            None => self.march(r),
+        }
+    }
+
+    /// Portions of the line of the ray that are inside of the interaction, if all items
+    /// are able to provide theirs
+    pub fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let (first, rest) = self.0.split_first()?;
+        let mut spans = first.intervals(r)?;
+        for item in rest {
+            spans = Span::intersection(&spans, &item.intervals(r)?);
+        }
+        for item in &self.1 {
+            spans = Span::difference(&spans, &item.intervals(r)?);
+        }
+        Some(spans)
+    }
+
+    /// Follow the ray through each item, and keep the first hit that passes the
+    /// inside/outside tests on all other items
+    fn march(&self, r: &Ray) -> HitRecord {
+        let mut record = HitRecord::Blank;
+        for i in 0..self.0.len() {
+            let mut ray = *r;
+            let mut offset = 0.0;
+            let item = &self.0[i];
+            loop {
+                match item.hit(&ray) {
+                    HitRecord::Blank => break,
+                    HitRecord::Hit(h) => {
+                        if Interaction::all_inside_except(h.pos, &self.0, i)
+                            && Interaction::all_outside_except(h.pos, &self.1, self.1.len())
+                        {
+                            record.compare(HitRecord::Hit(h.later(offset)));
+                        }
+                        ray.orig = h.pos + ray.dir * EPSILON;
+                        offset += h.t;
+                    }
+                }
+            }
+        }
+        for i in 0..self.1.len() {
+            let mut ray = *r;
+            let mut offset = 0.0;
+            let item = &self.1[i];
+            loop {
+                match item.hit(&ray) {
+                    HitRecord::Blank => break,
+                    HitRecord::Hit(h) => {
+                        if Interaction::all_inside_except(h.pos, &self.0, self.0.len())
+                            && Interaction::all_outside_except(h.pos, &self.1, i)
+                        {
+                            record.compare(HitRecord::Hit(h.later(offset)));
+                        }
+                        ray.orig = h.pos + ray.dir * EPSILON;
+                        offset += h.t;
+                    }
+                }
+            }
+        }
+        record
+    }
+
+    /// Check that a point is inside of the interaction
+    pub fn contains(&self, pos: Vec3) -> bool {
+        Interaction::all_inside_except(pos, &self.0, self.0.len())
+            && Interaction::all_outside_except(pos, &self.1, self.1.len())
+    }
+
+    /// Box that contains the whole surface and the inside of the interaction: all of
+    /// its points are inside of the bounded items of the first vector
+    pub fn bounds(&self) -> Option<Aabb> {
+        let mut res: Option<Aabb> = None;
+        for b in self.0.iter().filter_map(|item| item.bounds()) {
+            res = Some(res.map_or(b, |r| r.intersection(b)));
+        }
+        res
+    }
+
     /// The inside/outside test applied to the object that was hit may be unreliable, thus the
     /// final test is done on all but one of the items.
     ///
@@ -192,8 +309,8 @@ impl Interaction {
 /// The library can only manage set operations on single objects written as
 /// `Union[n=1 to N] ( (Intersection[i=1 to I] A_n,i) \ (Union[j=1 to J] B_n,j) )`.
 ///
-/// PyTrace shows that this is not a significant restriction, as any arbitrary set operation
-/// can be expressed in this canonical form (see `pytrace::external::interaction::canonical()`).
+/// This is not a significant restriction, as any arbitrary set operation can be expressed
+/// in this canonical form (see `Csg::canonical()`).
 pub type Composite = Vec<Interaction>;
 
 /// These are uniform textures that can be set for any object.
@@ -204,3 +321,15 @@ pub enum Texture {
     Light(RGB),
     Dielectric(RGB, f64),
 }
+
+impl Texture {
+    /// Same material with a different color
+    pub fn with_color(self, color: RGB) -> Self {
+        match self {
+            Texture::Lambertian(_) => Texture::Lambertian(color),
+            Texture::Metal(_, fuzz) => Texture::Metal(color, fuzz),
+            Texture::Light(_) => Texture::Light(color),
+            Texture::Dielectric(_, idx) => Texture::Dielectric(color, idx),
+        }
+    }
+}
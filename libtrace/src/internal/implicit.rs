@@ -0,0 +1,91 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Number of bisection steps once a change of sign has been found
+const BISECTION_STEPS: usize = 40;
+
+/// Surface described by an arbitrary equation `f(x, y, z) = 0`
+///
+/// Unlike `Sdf`, nothing is assumed about `f` except that it is negative inside the object
+/// and positive outside: the ray is sampled at regular intervals and a root is searched
+/// for by bisection whenever the sign changes.
+/// Details thinner than the sampling step may be missed.
+#[derive(Clone)]
+pub struct Implicit {
+    pub func: Arc<dyn Fn(Vec3) -> f64 + Send + Sync>,
+    /// Only the part of the surface inside this box is kept
+    pub bounds: Aabb,
+    /// Number of samples along the diagonal of `bounds`
+    pub resolution: usize,
+    pub texture: Texture,
+}
+
+impl Implicit {
+    pub fn build(self) -> Primitive {
+        Primitive(Arc::new(self))
+    }
+
+    /// Normal to the surface, calculated from the gradient of `func`
+    pub fn gradient(&self, p: Vec3) -> Vec3 {
+        let h = self.bounds.size().len() * 0.000_01;
+        let f = &self.func;
+        let dx = Vec3(h, 0., 0.);
+        let dy = Vec3(0., h, 0.);
+        let dz = Vec3(0., 0., h);
+        Vec3(
+            f(p + dx) - f(p - dx),
+            f(p + dy) - f(p - dy),
+            f(p + dz) - f(p - dz),
+        )
+    }
+}
+
+impl Hit for Implicit {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let (tmin, tmax) = match self.bounds.range(r) {
+            None => return HitRecord::Blank,
+            Some(range) => range,
+        };
+        let f = &self.func;
+        let step = self.bounds.size().len() / self.resolution as f64 / r.dir.len();
+        // Small offset so that a ray that starts on the surface does not find it again
+        let mut lo = tmin.max(step * 0.001).max(EPSILON);
+        // Invalid values (NaN) are considered to be outside
+        let mut inlo = f(r.project(lo)) < 0.;
+        while lo < tmax {
+            let hi = (lo + step).min(tmax);
+            let inhi = f(r.project(hi)) < 0.;
+            if inlo != inhi {
+                let (mut a, mut b) = (lo, hi);
+                for _ in 0..BISECTION_STEPS {
+                    let mid = (a + b) / 2.;
+                    if (f(r.project(mid)) < 0.) == inlo {
+                        a = mid;
+                    } else {
+                        b = mid;
+                    }
+                }
+                // Keep the end of the bracket that is past the root
+                let pos = r.project(b);
+                if self.bounds.contains(pos) {
+                    return HitRecord::make(b, pos, self.gradient(pos), self.texture);
+                }
+            }
+            lo = hi;
+            inlo = inhi;
+        }
+        HitRecord::Blank
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.bounds.contains(pos) && (self.func)(pos) < 0.
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Geometry shared by several instances
+pub struct Prototype {
+    groups: Composite,
+    /// Bounding box of each group
+    bounds: Vec<Option<Aabb>>,
+}
+
+impl Prototype {
+    /// Panics if there is no object to copy
+    pub fn new(groups: Composite) -> Arc<Self> {
+        if groups.iter().all(|g| g.0.is_empty()) {
+            panic!("A prototype needs at least one object");
+        }
+        let bounds = groups
+            .iter()
+            .map(|g| g.bounds().map(|b| b.pad(EPSILON)))
+            .collect();
+        Arc::new(Self { groups, bounds })
+    }
+
+    /// Box around all groups, if they are all bounded
+    pub fn bounds(&self) -> Option<Aabb> {
+        let mut res: Option<Aabb> = None;
+        for b in &self.bounds {
+            let b = (*b)?;
+            res = Some(res.map_or(b, |r| r.union(b)));
+        }
+        res
+    }
+}
+
+/// Copy of a prototype placed in the scene by a transformation
+///
+/// The geometry is not duplicated: each instance only costs a matrix, so thousands
+/// of identical objects take as much memory as one.
+///
+/// The scene has no hierarchy of boxes: like any other object, each instance is
+/// skipped by `world::nearest_hit` when the ray misses its box, then the groups of the
+/// prototype are culled the same way in local coordinates.
+#[derive(Clone)]
+pub struct Instance {
+    pub proto: Arc<Prototype>,
+    /// From the coordinates of the prototype to the coordinates of the scene
+    pub transform: Transform,
+    /// Replaces the textures of the prototype if set
+    pub texture: Option<Texture>,
+}
+
+impl Instance {
+    pub fn build(self) -> Primitive {
+        let bounds = self
+            .proto
+            .bounds()
+            .map(|b| b.transform(&self.transform.matrix));
+        Primitive(Arc::new(InstanceObject { inst: self, bounds }))
+    }
+}
+
+pub struct InstanceObject {
+    inst: Instance,
+    bounds: Option<Aabb>,
+}
+
+impl Hit for InstanceObject {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let local = self.inst.transform.unapply_ray(r);
+        let proto = &self.inst.proto;
+        match world::nearest_hit(&proto.groups, &proto.bounds, &local) {
+            HitRecord::Blank => HitRecord::Blank,
+            HitRecord::Hit(h) => HitRecord::make(
+                h.t,
+                r.project(h.t),
+                self.inst.transform.apply_normal(h.normal),
+                self.inst.texture.unwrap_or(h.texture),
+            ),
+        }
+    }
+
+    fn texture(&self) -> Texture {
+        match self.inst.texture {
+            Some(t) => t,
+            // `Prototype::new` ensures that there is such an object
+            None => self
+                .inst
+                .proto
+                .groups
+                .iter()
+                .find_map(|g| g.0.first())
+                .unwrap()
+                .texture(),
+        }
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        let p = self.inst.transform.inverse.apply_point(pos);
+        self.inst.proto.groups.iter().any(|g| g.contains(p))
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let local = self.inst.transform.unapply_ray(r);
+        let mut spans = Vec::new();
+        for g in &self.inst.proto.groups {
+            spans = Span::union(&spans, &g.intervals(&local)?);
+        }
+        for s in &mut spans {
+            for c in &mut [&mut s.enter, &mut s.exit] {
+                c.normal = self.inst.transform.apply_normal(c.normal);
+                if let Some(t) = self.inst.texture {
+                    c.texture = t;
+                }
+            }
+        }
+        Some(spans)
+    }
+}
@@ -0,0 +1,158 @@
+use crate::internal::*;
+
+/// Point where a line crosses the surface of a closed object
+#[derive(Clone, Copy)]
+pub struct Crossing {
+    /// Position along the ray, may be infinite for unbounded objects
+    pub t: f64,
+    /// Normal vector to the surface
+    pub normal: Vec3,
+    /// Texture of the crossed surface
+    pub texture: Texture,
+}
+
+impl Crossing {
+    pub fn new(t: f64, normal: Vec3, texture: Texture) -> Self {
+        Self { t, normal, texture }
+    }
+}
+
+/// Portion of a line that is inside of an object
+///
+/// Objects describe their interior along a ray as a sorted list of disjoint spans,
+/// computed along the whole line and not only in front of the origin of the ray, so
+/// that a ray starting inside of an object begins with a span that enters at a
+/// negative `t`.
+#[derive(Clone, Copy)]
+pub struct Span {
+    pub enter: Crossing,
+    pub exit: Crossing,
+}
+
+impl Span {
+    pub fn new(enter: Crossing, exit: Crossing) -> Self {
+        Self { enter, exit }
+    }
+
+    /// The whole line, for objects that contain the ray
+    pub fn everywhere(texture: Texture) -> Self {
+        let zero = Vec3(0., 0., 0.);
+        Self::new(
+            Crossing::new(-f64::INFINITY, zero, texture),
+            Crossing::new(f64::INFINITY, zero, texture),
+        )
+    }
+
+    /// Portion of the line between the two planes orthogonal to `normal` that go
+    /// through `p1` and `p2`
+    pub fn slab(r: &Ray, normal: Vec3, p1: Vec3, p2: Vec3, texture: Texture) -> Vec<Self> {
+        let dn = normal.dot(r.dir);
+        let d1 = normal.dot(p1 - r.orig);
+        let d2 = normal.dot(p2 - r.orig);
+        if dn.abs() < EPSILON {
+            // Parallel to the planes: either always or never between them
+            return if d1 * d2 < 0. {
+                vec![Self::everywhere(texture)]
+            } else {
+                Vec::new()
+            };
+        }
+        let (t1, t2) = (d1 / dn, d2 / dn);
+        vec![Self::new(
+            Crossing::new(t1.min(t2), normal, texture),
+            Crossing::new(t1.max(t2), normal, texture),
+        )]
+    }
+
+    /// Turn an even number of sorted crossings into spans, fails if they can't be paired
+    pub fn pair(crossings: &[Crossing]) -> Option<Vec<Self>> {
+        if crossings.len() % 2 == 1 {
+            return None;
+        }
+        Some(crossings.chunks(2).map(|c| Self::new(c[0], c[1])).collect())
+    }
+
+    /// Points that are inside of either list
+    pub fn union(a: &[Self], b: &[Self]) -> Vec<Self> {
+        let mut all = a.iter().chain(b.iter()).copied().collect::<Vec<_>>();
+        all.sort_by(|x, y| x.enter.t.partial_cmp(&y.enter.t).unwrap());
+        let mut res: Vec<Self> = Vec::new();
+        for s in all {
+            match res.last_mut() {
+                Some(last) if s.enter.t <= last.exit.t => {
+                    if s.exit.t > last.exit.t {
+                        last.exit = s.exit;
+                    }
+                }
+                _ => res.push(s),
+            }
+        }
+        res
+    }
+
+    /// Points that are inside of both lists
+    pub fn intersection(a: &[Self], b: &[Self]) -> Vec<Self> {
+        let mut res = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            let enter = if a[i].enter.t > b[j].enter.t {
+                a[i].enter
+            } else {
+                b[j].enter
+            };
+            let exit = if a[i].exit.t < b[j].exit.t {
+                a[i].exit
+            } else {
+                b[j].exit
+            };
+            if enter.t < exit.t {
+                res.push(Self::new(enter, exit));
+            }
+            // Whichever span ends first can't overlap anything else
+            if a[i].exit.t < b[j].exit.t {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        res
+    }
+
+    /// Points that are inside of the first list but outside of the second one
+    pub fn difference(a: &[Self], b: &[Self]) -> Vec<Self> {
+        let mut res = Vec::new();
+        let mut j = 0;
+        for s in a {
+            let mut enter = s.enter;
+            while j < b.len() && b[j].exit.t <= enter.t {
+                j += 1;
+            }
+            let mut k = j;
+            while k < b.len() && b[k].enter.t < s.exit.t {
+                if enter.t < b[k].enter.t {
+                    res.push(Self::new(enter, b[k].enter));
+                }
+                if enter.t < b[k].exit.t {
+                    enter = b[k].exit;
+                }
+                k += 1;
+            }
+            if enter.t < s.exit.t {
+                res.push(Self::new(enter, s.exit));
+            }
+        }
+        res
+    }
+
+    /// Nearest boundary in front of the origin of the ray
+    pub fn first_hit(spans: &[Self], r: &Ray) -> HitRecord {
+        for s in spans {
+            for c in &[s.enter, s.exit] {
+                if EPSILON < c.t && c.t.is_finite() {
+                    return HitRecord::make(c.t, r.project(c.t), c.normal, c.texture);
+                }
+            }
+        }
+        HitRecord::Blank
+    }
+}
@@ -0,0 +1,247 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Adjacent faces of the profile that make an angle smaller than this are shaded
+/// as a smooth surface
+const SMOOTH_COS: f64 = 0.866;
+
+/// Surface of revolution: a profile is turned around an axis
+///
+/// The profile is a polyline of points `(radius, height)`, from the base upwards.
+/// It is closed by joining both ends to the axis, which makes the lathe a closed object
+/// whose inside is well-defined.
+#[derive(Clone)]
+pub struct Lathe {
+    /// Point of the axis at height 0
+    pub base: Vec3,
+    /// Direction of the axis, heights are measured in multiples of its length
+    pub axis: Vec3,
+    /// Points `(radius, height)` of the silhouette, all radii should be positive
+    pub profile: Vec<(f64, f64)>,
+    pub texture: Texture,
+}
+
+impl Lathe {
+    /// Lathe whose profile is a smooth curve going through all `points`,
+    /// approximated by `subdivisions` segments between each pair of points
+    pub fn spline(
+        base: Vec3,
+        axis: Vec3,
+        points: &[(f64, f64)],
+        subdivisions: usize,
+        texture: Texture,
+    ) -> Self {
+        let pts = points
+            .iter()
+            .map(|&(r, h)| Vec3(r, h, 0.))
+            .collect::<Vec<_>>();
+        let profile = spline::catmull_rom_chain(&pts, subdivisions.max(1))
+            .into_iter()
+            .map(|p| (p.0.max(0.), p.1))
+            .collect();
+        Self {
+            base,
+            axis,
+            profile,
+            texture,
+        }
+    }
+
+    pub fn build(self) -> Primitive {
+        if self.profile.len() < 2 {
+            panic!("A lathe needs at least two points in its profile");
+        }
+        let scale = self.axis.len();
+        let axis = self.axis / scale;
+        let mut polygon = self
+            .profile
+            .iter()
+            .map(|&(r, h)| (r, h * scale))
+            .collect::<Vec<_>>();
+        let first = polygon[0];
+        let last = polygon[polygon.len() - 1];
+        if last.0 > 0. {
+            polygon.push((0., last.1));
+        }
+        if first.0 > 0. {
+            polygon.insert(0, (0., first.1));
+        }
+        let n = polygon.len();
+        let area = (0..n).fold(0., |acc, i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            acc + a.0 * b.1 - b.0 * a.1
+        });
+        let orient = if area > 0. { 1. } else { -1. };
+        // Outward normal of each edge, in the (radius, height) plane
+        let flat = (0..n)
+            .map(|i| {
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                let (dr, dh) = (b.0 - a.0, b.1 - a.1);
+                let len = (dr * dr + dh * dh).sqrt().max(f64::EPSILON);
+                (dh / len * orient, -dr / len * orient)
+            })
+            .collect::<Vec<_>>();
+        let smooth = |a: (f64, f64), b: (f64, f64)| {
+            if a.0 * b.0 + a.1 * b.1 > SMOOTH_COS {
+                let (r, h) = (a.0 + b.0, a.1 + b.1);
+                let len = (r * r + h * h).sqrt();
+                (r / len, h / len)
+            } else {
+                a
+            }
+        };
+        let mut segments = Vec::new();
+        for i in 0..n {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            if a.0 <= 0. && b.0 <= 0. {
+                // Along the axis, this edge has no surface
+                continue;
+            }
+            segments.push(Segment {
+                r0: a.0,
+                h0: a.1,
+                r1: b.0,
+                h1: b.1,
+                n0: smooth(flat[i], flat[(i + n - 1) % n]),
+                n1: smooth(flat[i], flat[(i + 1) % n]),
+            });
+        }
+        let rmax = polygon.iter().fold(0., |m: f64, p| m.max(p.0));
+        let (hmin, hmax) = polygon
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p.1), hi.max(p.1))
+            });
+        let r = Vec3(rmax, rmax, rmax);
+        let bottom = self.base + axis * hmin;
+        let top = self.base + axis * hmax;
+        let bounds = Aabb::around(&[bottom - r, bottom + r, top - r, top + r]).pad(EPSILON);
+        Primitive(Arc::new(LatheObject {
+            base: self.base,
+            axis,
+            segments,
+            polygon,
+            bounds,
+            texture: self.texture,
+        }))
+    }
+}
+
+/// One edge of the profile, which becomes a portion of a cone once revolved
+struct Segment {
+    r0: f64,
+    h0: f64,
+    r1: f64,
+    h1: f64,
+    /// Shading normals at both ends
+    n0: (f64, f64),
+    n1: (f64, f64),
+}
+
+pub struct LatheObject {
+    base: Vec3,
+    /// Unit vector
+    axis: Vec3,
+    segments: Vec<Segment>,
+    /// Closed profile
+    polygon: Vec<(f64, f64)>,
+    bounds: Aabb,
+    texture: Texture,
+}
+
+impl LatheObject {
+    /// Normal at position `s` (between 0 and 1) of a segment, `radial` is the
+    /// component of the point orthogonal to the axis
+    fn normal(&self, seg: &Segment, s: f64, radial: Vec3) -> Vec3 {
+        let nr = seg.n0.0 * (1. - s) + seg.n1.0 * s;
+        let nh = seg.n0.1 * (1. - s) + seg.n1.1 * s;
+        let radial = if radial.dot_self() > 0. {
+            radial.unit()
+        } else {
+            radial
+        };
+        radial * nr + self.axis * nh
+    }
+}
+
+impl Hit for LatheObject {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        if self.bounds.range(r).is_none() {
+            return HitRecord::Blank;
+        }
+        let o = r.orig - self.base;
+        let oh = o.dot(self.axis);
+        let dh = r.dir.dot(self.axis);
+        let op = o - self.axis * oh;
+        let dp = r.dir - self.axis * dh;
+        let mut rec = HitRecord::Blank;
+        for seg in &self.segments {
+            let mut candidates = Vec::new();
+            if (seg.h1 - seg.h0).abs() < EPSILON {
+                // Flat ring
+                if dh.abs() > EPSILON * EPSILON {
+                    let t = (seg.h0 - oh) / dh;
+                    let rad = (op + dp * t).len();
+                    let (lo, hi) = (seg.r0.min(seg.r1), seg.r0.max(seg.r1));
+                    if lo <= rad && rad <= hi {
+                        candidates.push((t, (rad - seg.r0) / (seg.r1 - seg.r0)));
+                    }
+                }
+            } else {
+                // Cone: the radius varies linearly with the height
+                let k = (seg.r1 - seg.r0) / (seg.h1 - seg.h0);
+                let a = seg.r0 - k * seg.h0 + k * oh;
+                let roots = poly::quadratic(
+                    dp.dot_self() - k * k * dh * dh,
+                    2. * (op.dot(dp) - k * dh * a),
+                    op.dot_self() - a * a,
+                );
+                for t in roots {
+                    let s = (oh + dh * t - seg.h0) / (seg.h1 - seg.h0);
+                    if (-EPSILON..=1. + EPSILON).contains(&s) {
+                        candidates.push((t, s));
+                    }
+                }
+            }
+            for (t, s) in candidates {
+                if EPSILON < t {
+                    let radial = op + dp * t;
+                    rec.compare(HitRecord::make(
+                        t,
+                        r.project(t),
+                        self.normal(seg, s.clamp(0., 1.), radial),
+                        self.texture,
+                    ));
+                }
+            }
+        }
+        rec
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        let p = pos - self.base;
+        let h = p.dot(self.axis);
+        let rad = (p - self.axis * h).len();
+        // Even-odd rule in the (radius, height) plane
+        let n = self.polygon.len();
+        let mut inside = false;
+        for i in 0..n {
+            let (a, b) = (self.polygon[i], self.polygon[(i + 1) % n]);
+            if (a.1 > h) != (b.1 > h) {
+                let cross = a.0 + (h - a.1) / (b.1 - a.1) * (b.0 - a.0);
+                if rad < cross {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
@@ -0,0 +1,259 @@
+use crate::internal::*;
+use std::ops;
+
+/// Linear transformation of the space (rotation, scaling, shearing)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat3(pub [[f64; 3]; 3]);
+
+impl Mat3 {
+    pub fn identity() -> Self {
+        Self::diagonal(Vec3(1., 1., 1.))
+    }
+
+    /// Scale by a different factor along each axis
+    pub fn diagonal(v: Vec3) -> Self {
+        Mat3([[v.0, 0., 0.], [0., v.1, 0.], [0., 0., v.2]])
+    }
+
+    /// Matrix whose columns are the images of the three axes
+    pub fn from_columns(x: Vec3, y: Vec3, z: Vec3) -> Self {
+        Mat3([[x.0, y.0, z.0], [x.1, y.1, z.1], [x.2, y.2, z.2]])
+    }
+
+    /// Rotation of `angle` degrees around `axis`, counterclockwise when the axis
+    /// points towards the viewer
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        let Vec3(x, y, z) = axis.unit();
+        let (s, c) = angle.to_radians().sin_cos();
+        let k = 1. - c;
+        Mat3([
+            [c + x * x * k, x * y * k - z * s, x * z * k + y * s],
+            [y * x * k + z * s, c + y * y * k, y * z * k - x * s],
+            [z * x * k - y * s, z * y * k + x * s, c + z * z * k],
+        ])
+    }
+
+    pub fn column(&self, j: usize) -> Vec3 {
+        Vec3(self.0[0][j], self.0[1][j], self.0[2][j])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_columns(
+            Vec3(self.0[0][0], self.0[0][1], self.0[0][2]),
+            Vec3(self.0[1][0], self.0[1][1], self.0[1][2]),
+            Vec3(self.0[2][0], self.0[2][1], self.0[2][2]),
+        )
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.column(0).dot(self.column(1).cross(self.column(2)))
+    }
+
+    /// Inverse computed from cross products of the columns, panics if the matrix
+    /// is singular
+    pub fn inverse(&self) -> Self {
+        self.try_inverse()
+            .expect("Transformation matrix is not invertible")
+    }
+
+    /// Inverse, or `None` if the matrix is singular
+    pub fn try_inverse(&self) -> Option<Self> {
+        let (x, y, z) = (self.column(0), self.column(1), self.column(2));
+        let det = self.determinant();
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+        // The rows of the inverse form the dual basis of the columns
+        Some(Self::from_columns(y.cross(z) / det, z.cross(x) / det, x.cross(y) / det).transpose())
+    }
+
+    pub fn apply(&self, v: Vec3) -> Vec3 {
+        self.column(0) * v.0 + self.column(1) * v.1 + self.column(2) * v.2
+    }
+}
+
+/// Composition: `(a * b)` applies `b` first, then `a`
+impl ops::Mul for Mat3 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::from_columns(
+            self.apply(other.column(0)),
+            self.apply(other.column(1)),
+            self.apply(other.column(2)),
+        )
+    }
+}
+
+/// Affine transformation of the space, as a 4x4 matrix acting on homogeneous coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4(pub [[f64; 4]; 4]);
+
+impl Mat4 {
+    pub fn identity() -> Self {
+        Self::affine(Mat3::identity(), Vec3(0., 0., 0.))
+    }
+
+    /// Linear part `m` followed by a translation
+    pub fn affine(m: Mat3, t: Vec3) -> Self {
+        let m = m.0;
+        Mat4([
+            [m[0][0], m[0][1], m[0][2], t.0],
+            [m[1][0], m[1][1], m[1][2], t.1],
+            [m[2][0], m[2][1], m[2][2], t.2],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn translation(v: Vec3) -> Self {
+        Self::affine(Mat3::identity(), v)
+    }
+
+    /// Scale by a different factor along each axis
+    pub fn scaling(v: Vec3) -> Self {
+        Self::affine(Mat3::diagonal(v), Vec3(0., 0., 0.))
+    }
+
+    /// Rotation of `angle` degrees around `axis`, counterclockwise when the axis
+    /// points towards the viewer
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        Self::affine(Mat3::rotation(axis, angle), Vec3(0., 0., 0.))
+    }
+
+    /// Upper left 3x3 block, which acts on vectors
+    pub fn linear(&self) -> Mat3 {
+        let m = &self.0;
+        Mat3([
+            [m[0][0], m[0][1], m[0][2]],
+            [m[1][0], m[1][1], m[1][2]],
+            [m[2][0], m[2][1], m[2][2]],
+        ])
+    }
+
+    pub fn translation_part(&self) -> Vec3 {
+        Vec3(self.0[0][3], self.0[1][3], self.0[2][3])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x = self.0[j][i];
+            }
+        }
+        Mat4(m)
+    }
+
+    /// Inverse by Gauss-Jordan elimination, panics if the matrix is singular
+    pub fn inverse(&self) -> Self {
+        self.try_inverse()
+            .expect("Transformation matrix is not invertible")
+    }
+
+    /// Inverse, or `None` if the matrix is singular
+    pub fn try_inverse(&self) -> Option<Self> {
+        let mut a = self.0;
+        let mut inv = Self::identity().0;
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())
+                .unwrap();
+            if a[pivot][col].abs() < f64::EPSILON {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            let p = a[col][col];
+            for j in 0..4 {
+                a[col][j] /= p;
+                inv[col][j] /= p;
+            }
+            for i in 0..4 {
+                if i != col {
+                    let f = a[i][col];
+                    for j in 0..4 {
+                        a[i][j] -= f * a[col][j];
+                        inv[i][j] -= f * inv[col][j];
+                    }
+                }
+            }
+        }
+        Some(Mat4(inv))
+    }
+
+    /// Image of a position, affected by translations
+    pub fn apply_point(&self, p: Vec3) -> Vec3 {
+        self.apply_vector(p) + self.translation_part()
+    }
+
+    /// Image of a direction, not affected by translations
+    pub fn apply_vector(&self, v: Vec3) -> Vec3 {
+        self.linear().apply(v)
+    }
+}
+
+/// Composition: `(a * b)` applies `b` first, then `a`
+impl ops::Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut m = [[0.; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x = (0..4).map(|k| self.0[i][k] * other.0[k][j]).sum();
+            }
+        }
+        Mat4(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).len() < 1e-9
+    }
+
+    #[test]
+    fn rotation_quarter_turn() {
+        let m = Mat3::rotation(Vec3(0., 0., 1.), 90.);
+        assert!(close(m.apply(Vec3(1., 0., 0.)), Vec3(0., 1., 0.)));
+        assert!(close(m.apply(Vec3(0., 1., 0.)), Vec3(-1., 0., 0.)));
+        assert!((m.determinant() - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn mat3_inverse() {
+        let m = Mat3::rotation(Vec3(1., 2., 3.), 37.) * Mat3::diagonal(Vec3(2., 0.5, 3.));
+        let v = Vec3(0.3, -1.2, 4.);
+        assert!(close(m.inverse().apply(m.apply(v)), v));
+        assert!(close((m * m.inverse()).apply(v), v));
+    }
+
+    #[test]
+    fn mat4_inverse() {
+        let m = Mat4::translation(Vec3(1., -2., 3.))
+            * Mat4::rotation(Vec3(0., 1., 1.), 120.)
+            * Mat4::scaling(Vec3(2., 3., 0.5));
+        let p = Vec3(-1., 0.5, 2.);
+        assert!(close(m.inverse().apply_point(m.apply_point(p)), p));
+        assert!(close(m.inverse().apply_vector(m.apply_vector(p)), p));
+    }
+
+    #[test]
+    fn composition_order() {
+        let t = Mat4::translation(Vec3(1., 0., 0.));
+        let s = Mat4::scaling(Vec3(2., 2., 2.));
+        // Scale first, then translate
+        assert!(close(
+            (t * s).apply_point(Vec3(1., 1., 1.)),
+            Vec3(3., 2., 2.)
+        ));
+        // Translation does not affect vectors
+        assert!(close(
+            (t * s).apply_vector(Vec3(1., 1., 1.)),
+            Vec3(2., 2., 2.)
+        ));
+    }
+}
@@ -0,0 +1,379 @@
+use crate::internal::*;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+
+/// Maximum number of triangles in a leaf of the bounding volume hierarchy
+const LEAF_SIZE: usize = 4;
+
+/// A surface made of triangles that share their vertices
+///
+/// If the mesh is watertight (every edge is shared by exactly two faces), it has a
+/// well-defined interior and can be used in intersections and differences like any
+/// other solid.
+#[derive(Clone)]
+pub struct Mesh {
+    pub vertices: Vec<Vec3>,
+    /// Normal at each vertex, used for smooth shading.
+    /// Faces are flat if this is empty.
+    pub normals: Vec<Vec3>,
+    /// Indices in `vertices` of the three corners of each face
+    pub faces: Vec<[usize; 3]>,
+    pub texture: Texture,
+}
+
+impl Mesh {
+    /// Load from a Wavefront `.obj` file.
+    ///
+    /// Only vertices (`v`) and faces (`f`) are read, polygonal faces are split into triangles.
+    /// Fails with `InvalidData` on malformed or non-finite coordinates, and on invalid indices.
+    pub fn load(file: &str, texture: Texture) -> io::Result<Self> {
+        let obj = fs::read_to_string(file)?;
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for (n, line) in obj.lines().enumerate() {
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: malformed line `{}`", file, n + 1, line),
+                )
+            };
+            let mut it = line.split_whitespace();
+            match it.next() {
+                Some("v") => {
+                    let mut get = || {
+                        it.next()
+                            .and_then(|s| s.parse::<f64>().ok())
+                            .filter(|x| x.is_finite())
+                            .ok_or_else(invalid)
+                    };
+                    vertices.push(Vec3(get()?, get()?, get()?));
+                }
+                Some("f") => {
+                    // Indices start at 1, negative values are relative to the end of the list
+                    let idx = it
+                        .map(|s| {
+                            let i = s.split('/').next()?.parse::<isize>().ok()?;
+                            let i = if i < 0 {
+                                vertices.len() as isize + i
+                            } else {
+                                i - 1
+                            };
+                            if 0 <= i && (i as usize) < vertices.len() {
+                                Some(i as usize)
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(invalid)?;
+                    for k in 1..idx.len().saturating_sub(1) {
+                        faces.push([idx[0], idx[k], idx[k + 1]]);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(Self {
+            vertices,
+            normals: Vec::new(),
+            faces,
+            texture,
+        })
+    }
+
+    /// Shade the mesh as a smooth surface, each vertex takes the average of the normals
+    /// of the faces around it, weighted by their area.
+    pub fn smooth(self) -> Self {
+        let mut normals = vec![Vec3(0., 0., 0.); self.vertices.len()];
+        for &[a, b, c] in &self.faces {
+            let n =
+                (self.vertices[b] - self.vertices[a]).cross(self.vertices[c] - self.vertices[a]);
+            for &i in &[a, b, c] {
+                normals[i] += n;
+            }
+        }
+        Self {
+            normals: normals.into_iter().map(|n| n.unit()).collect(),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Primitive {
+        let triangles = self
+            .faces
+            .iter()
+            .map(|&[a, b, c]| Triangle {
+                a: self.vertices[a],
+                u: self.vertices[b] - self.vertices[a],
+                v: self.vertices[c] - self.vertices[a],
+                texture: self.texture,
+            })
+            .collect::<Vec<_>>();
+        let normals = if self.normals.is_empty() {
+            Vec::new()
+        } else {
+            self.faces
+                .iter()
+                .map(|&[a, b, c]| [self.normals[a], self.normals[b], self.normals[c]])
+                .collect()
+        };
+        let mut idx = (0..triangles.len()).collect::<Vec<_>>();
+        let tree = Node::new(&triangles, &mut idx);
+        Primitive(Arc::new(MeshObject {
+            triangles,
+            normals,
+            tree,
+            texture: self.texture,
+        }))
+    }
+}
+
+/// Bounding volume hierarchy over the faces of a mesh
+enum Node {
+    Leaf(Aabb, Vec<usize>),
+    Branch(Aabb, Box<Node>, Box<Node>),
+}
+
+/// Same as `Triangle::hit`, except that edges are included so that rays can't slip
+/// between two adjacent faces.
+///
+/// If `normals` are given, the normal is interpolated between the three corners.
+fn hit_triangle(t: &Triangle, normals: Option<&[Vec3; 3]>, r: &Ray) -> HitRecord {
+    let n = t.u.cross(t.v);
+    let det = -n.dot(r.dir);
+    if det.abs() < EPSILON * EPSILON {
+        return HitRecord::Blank;
+    }
+    let w = r.orig - t.a;
+    let a = -w.cross(t.v).dot(r.dir) / det;
+    let b = -t.u.cross(w).dot(r.dir) / det;
+    let temp = n.dot(w) / det;
+    if a >= 0. && b >= 0. && a + b <= 1. && EPSILON < temp {
+        let normal = match normals {
+            Some([na, nb, nc]) => *na * (1. - a - b) + *nb * a + *nc * b,
+            None => n,
+        };
+        HitRecord::make(temp, r.project(temp), normal, t.texture)
+    } else {
+        HitRecord::Blank
+    }
+}
+
+fn triangle_bounds(t: &Triangle) -> Aabb {
+    Aabb::around(&[t.a, t.a + t.u, t.a + t.v])
+}
+
+impl Node {
+    fn new(tris: &[Triangle], idx: &mut [usize]) -> Self {
+        let bounds = idx
+            .iter()
+            .map(|&i| triangle_bounds(&tris[i]))
+            .fold(Aabb::around(&[]), Aabb::union)
+            .pad(EPSILON);
+        if idx.len() <= LEAF_SIZE {
+            return Node::Leaf(bounds, idx.to_vec());
+        }
+        // Split along the longest axis at the median of the centroids
+        let size = bounds.size();
+        let key = |i: &usize| {
+            let c = triangle_bounds(&tris[*i]).center();
+            if size.0 >= size.1 && size.0 >= size.2 {
+                c.0
+            } else if size.1 >= size.2 {
+                c.1
+            } else {
+                c.2
+            }
+        };
+        idx.sort_by(|a, b| key(a).total_cmp(&key(b)));
+        let (lt, rt) = idx.split_at_mut(idx.len() / 2);
+        Node::Branch(
+            bounds,
+            Box::new(Node::new(tris, lt)),
+            Box::new(Node::new(tris, rt)),
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        match self {
+            Node::Leaf(b, _) | Node::Branch(b, _, _) => *b,
+        }
+    }
+
+    fn hit(&self, tris: &[Triangle], normals: &[[Vec3; 3]], r: &Ray, rec: &mut HitRecord) {
+        match self.bounds().range(r) {
+            None => return,
+            Some((_, tmax)) if tmax < EPSILON => return,
+            Some((tmin, _)) => {
+                if let HitRecord::Hit(h) = rec {
+                    if h.t < tmin {
+                        return;
+                    }
+                }
+            }
+        }
+        match self {
+            Node::Leaf(_, v) => {
+                for &i in v {
+                    rec.compare(hit_triangle(&tris[i], normals.get(i), r));
+                }
+            }
+            Node::Branch(_, lt, rt) => {
+                lt.hit(tris, normals, r, rec);
+                rt.hit(tris, normals, r, rec);
+            }
+        }
+    }
+}
+
+pub struct MeshObject {
+    triangles: Vec<Triangle>,
+    /// Normals at the corners of each triangle, empty for flat shading
+    normals: Vec<[Vec3; 3]>,
+    tree: Node,
+    texture: Texture,
+}
+
+impl MeshObject {
+    /// [Generalized winding number](https://igl.ethz.ch/projects/winding-number/)
+    ///
+    /// Sum of the signed solid angles under which each face is seen from `pos`, divided by 4π.
+    /// It is ±1 inside a closed mesh and 0 outside, and degrades gracefully for meshes
+    /// that have small holes.
+    pub fn winding_number(&self, pos: Vec3) -> f64 {
+        let mut total = 0.0;
+        for t in &self.triangles {
+            let a = t.a - pos;
+            let b = t.a + t.u - pos;
+            let c = t.a + t.v - pos;
+            let (la, lb, lc) = (a.len(), b.len(), c.len());
+            let num = a.dot(b.cross(c));
+            let den = la * lb * lc + a.dot(b) * lc + b.dot(c) * la + c.dot(a) * lb;
+            total += 2. * num.atan2(den);
+        }
+        total / (4. * std::f64::consts::PI)
+    }
+}
+
+impl Hit for MeshObject {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let mut rec = HitRecord::Blank;
+        self.tree.hit(&self.triangles, &self.normals, r, &mut rec);
+        rec
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.tree.bounds().contains(pos) && self.winding_number(pos).abs() > 0.5
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.tree.bounds())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn load_str(test: &str, obj: &str) -> io::Result<Mesh> {
+        let path = std::env::temp_dir().join(format!("mesh-{}-{}.obj", test, std::process::id()));
+        fs::write(&path, obj).unwrap();
+        let mesh = Mesh::load(path.to_str().unwrap(), Texture::Lambertian(RGB(1., 1., 1.)));
+        fs::remove_file(&path).unwrap();
+        mesh
+    }
+
+    #[test]
+    fn load_quad() {
+        let obj = "# square\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1/1 2/2 3/3 -1\n";
+        let mesh = load_str("quad", obj).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.faces, vec![[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn cube_inside() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+                   f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 4 8 7 3\nf 1 5 8 4\nf 2 3 7 6\n";
+        let cube = load_str("cube", obj).unwrap().build();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let pos = Vec3(
+                rng.gen_range(-0.5, 1.5),
+                rng.gen_range(-0.5, 1.5),
+                rng.gen_range(-0.5, 1.5),
+            );
+            let coords = [pos.0, pos.1, pos.2];
+            // Stay away from the faces, where rounding errors decide
+            if coords
+                .iter()
+                .any(|x| x.abs() < 1e-3 || (x - 1.).abs() < 1e-3)
+            {
+                continue;
+            }
+            let expected = coords.iter().all(|&x| 0. < x && x < 1.);
+            assert_eq!(cube.inside(pos), expected, "{:?}", coords);
+        }
+        // Just inside and just outside of the faces
+        assert!(cube.inside(Vec3(0.999, 0.001, 0.5)));
+        assert!(!cube.inside(Vec3(1.001, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn tetrahedron_inside() {
+        // Faces in both orientations, the winding number only changes sign
+        for obj in &[
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n",
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 2 3\nf 1 4 2\nf 1 3 4\nf 2 4 3\n",
+        ] {
+            let tetra = load_str("tetrahedron", obj).unwrap().build();
+            assert!(tetra.inside(Vec3(0.2, 0.2, 0.2)));
+            assert!(tetra.inside(Vec3(0.01, 0.01, 0.9)));
+            assert!(!tetra.inside(Vec3(0.4, 0.4, 0.4)));
+            assert!(!tetra.inside(Vec3(-0.1, 0.2, 0.2)));
+            assert!(!tetra.inside(Vec3(2., 2., 2.)));
+        }
+    }
+
+    #[test]
+    fn build_degenerate() {
+        // Enough faces to split the hierarchy, one of them with a NaN corner
+        let mut vertices = (0..8)
+            .map(|i| Vec3(i as f64, (i % 2) as f64, 0.))
+            .collect::<Vec<_>>();
+        vertices[3].0 = f64::NAN;
+        let mesh = Mesh {
+            vertices,
+            normals: Vec::new(),
+            faces: (0..6).map(|i| [i, i + 1, i + 2]).collect(),
+            texture: Texture::Lambertian(RGB(0.5, 0.5, 0.5)),
+        };
+        let obj = mesh.build();
+        let ray = Ray::new(Vec3(0.5, 0.4, 1.), Vec3(0., 0., -1.));
+        assert!(matches!(obj.hit(&ray), HitRecord::Hit(_)));
+    }
+
+    #[test]
+    fn load_malformed() {
+        for (test, obj) in &[
+            ("coord", "v 0 0 x\n"),
+            ("nan", "v nan 0 0\n"),
+            ("inf", "v 0 inf 0\n"),
+            ("short", "v 0 0\n"),
+            ("index", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"),
+            ("zero", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n"),
+        ] {
+            let err = load_str(test, obj).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        let missing = Mesh::load("/nonexistent.obj", Texture::Lambertian(RGB(1., 1., 1.)));
+        assert_eq!(missing.err().unwrap().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -1,26 +1,91 @@
+/// Bounding boxes
+pub mod aabb;
 /// Abstaction for the field of view
 pub mod camera;
+/// Cameras that travel along curves
+pub mod camera_path;
+/// Arbitrary set operations on objects
+pub mod csg;
+/// Prisms with a polygonal base
+pub mod extrusion;
+/// Terrain from a grid of altitudes
+pub mod heightfield;
 /// Wrappers, trait & textures
 pub mod hitable;
+/// Surfaces defined by an arbitrary equation
+pub mod implicit;
+/// Geometry shared between several copies of an object
+pub mod instance;
+/// Portions of a ray inside of objects
+pub mod interval;
+/// Surfaces of revolution
+pub mod lathe;
+/// 3x3 and 4x4 matrices
+pub mod matrix;
+/// Closed surfaces made of triangles
+pub mod mesh;
+/// Objects that move during the exposure
+pub mod motion;
+/// Curved surfaces defined by control points
+pub mod patch;
+/// Polynomial root finding
+pub mod poly;
 /// Basic objects to build complex scenes
 pub mod primitives;
+/// Surfaces of degree 2
+pub mod quadric;
+/// Quaternions to represent rotations
+pub mod quat;
 /// Simple representation of a light ray
 pub mod ray;
 /// Operations on color values
 pub mod rgb;
+/// Objects described by their distance function
+pub mod sdf;
 /// External environment texture
 pub mod sky;
+/// Smooth curves through control points
+pub mod spline;
+/// Keyframed animation of scenes
+pub mod timeline;
+/// Affine transformations and their inverse
+pub mod transform;
+/// Objects moved, turned or stretched
+pub mod transformed;
+/// Tubes swept along curves
+pub mod tube;
 /// Used for both positions and 3D-vectors
 pub mod vec3;
 /// Main loop & wrapper struct
 pub mod world;
 
-pub use camera::Camera;
+pub use aabb::Aabb;
+pub use camera::{Camera, Convergence, PhysicalCamera, Projection};
+pub use camera_path::{Aim, CameraPath};
+pub use csg::{Csg, CsgObject};
+pub use extrusion::Extrusion;
+pub use heightfield::HeightField;
 pub use hitable::*;
+pub use implicit::Implicit;
+pub use instance::{Instance, Prototype};
+pub use interval::{Crossing, Span};
+pub use lathe::Lathe;
+pub use matrix::{Mat3, Mat4};
+pub use mesh::*;
+pub use motion::{Keyframe, Motion, Moving};
+pub use patch::BezierPatch;
 pub use primitives::*;
+pub use quadric::*;
+pub use quat::Quat;
 pub use ray::Ray;
 pub use rgb::RGB;
+pub use sdf::Sdf;
 pub use sky::Sky;
+pub use spline::Path;
+pub use timeline::{Animatable, Animated, CameraKey, Interpolation, Timeline, Track};
+pub use transform::Transform;
+pub use transformed::{Transformable, Transformed};
+pub use tube::{Curve, Tube};
 pub use vec3::Vec3;
 pub use world::World;
 
@@ -0,0 +1,203 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Position of an object at a given time
+#[derive(Clone, Copy)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// Scale along each axis, applied before the rotation
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    /// Object turned by `rotation` around the origin, then moved by `translation`
+    pub fn new(time: f64, translation: Vec3, rotation: Quat) -> Self {
+        Self {
+            time,
+            translation,
+            rotation,
+            scale: Vec3(1., 1., 1.),
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::translation(self.translation)
+            * Transform::from_quat(self.rotation)
+            * Transform::scaling(self.scale)
+    }
+
+    /// Linear interpolation of the translation and scale, spherical interpolation of
+    /// the rotation
+    fn between(&self, other: &Self, t: f64) -> Self {
+        Self {
+            time: self.time + (other.time - self.time) * t,
+            translation: self.translation + (other.translation - self.translation) * t,
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale + (other.scale - self.scale) * t,
+        }
+    }
+}
+
+/// Transformation that changes over time
+#[derive(Clone)]
+pub enum Motion {
+    /// Translation by `velocity * time`
+    Linear(Vec3),
+    /// Keyframes sorted by time, the object stays still before the first one and after
+    /// the last one
+    Keyframes(Vec<Keyframe>),
+}
+
+impl Motion {
+    /// Motion through the keyframes, which are sorted by time
+    pub fn keyframes(mut keys: Vec<Keyframe>) -> Self {
+        if keys.is_empty() {
+            panic!("A motion needs at least one keyframe");
+        }
+        keys.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        Motion::Keyframes(keys)
+    }
+
+    /// Transformation from the coordinates of the object to the coordinates of the scene
+    pub fn at(&self, time: f64) -> Transform {
+        match self {
+            Motion::Linear(velocity) => Transform::translation(*velocity * time),
+            Motion::Keyframes(keys) => {
+                let next = keys.iter().position(|k| k.time > time);
+                match next {
+                    Some(0) => keys[0].transform(),
+                    None => keys[keys.len() - 1].transform(),
+                    Some(i) => {
+                        let (a, b) = (&keys[i - 1], &keys[i]);
+                        a.between(b, (time - a.time) / (b.time - a.time))
+                            .transform()
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Any object whose position depends on the time of the ray
+///
+/// The inside/outside test has no notion of time and uses the position at time 0, so
+/// moving items of an interaction should provide their intervals.
+pub struct Moving {
+    pub obj: Primitive,
+    pub motion: Motion,
+}
+
+impl Moving {
+    pub fn build(self) -> Primitive {
+        Primitive(Arc::new(self))
+    }
+
+    /// Object as it is seen by the ray
+    fn at(&self, r: &Ray) -> Transformed {
+        Transformed {
+            obj: self.obj.clone(),
+            transform: self.motion.at(r.time),
+        }
+    }
+}
+
+impl Hit for Moving {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        self.at(r).hit(r)
+    }
+
+    fn texture(&self) -> Texture {
+        self.obj.texture()
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.obj.inside(self.motion.at(0.).inverse.apply_point(pos))
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        self.at(r).intervals(r)
+    }
+
+    /// Boxes of the object at the keyframes and at regular steps between them, padded
+    /// by the distance between the path of the object and the straight line between
+    /// two steps
+    ///
+    /// A linear motion goes on forever and is not bounded.
+    fn bounds(&self) -> Option<Aabb> {
+        let keys = match &self.motion {
+            Motion::Linear(_) => return None,
+            Motion::Keyframes(keys) => keys,
+        };
+        let b = self.obj.bounds()?;
+        let at = |k: &Keyframe| b.transform(&k.transform().matrix);
+        let mut corners = Vec::new();
+        for &x in &[b.min.0, b.max.0] {
+            for &y in &[b.min.1, b.max.1] {
+                for &z in &[b.min.2, b.max.2] {
+                    corners.push(Vec3(x, y, z));
+                }
+            }
+        }
+        let mut res = at(&keys[0]);
+        let mut pad = EPSILON;
+        for w in keys.windows(2) {
+            let (k0, k1) = (&w[0], &w[1]);
+            let cos = k0.rotation.normalize().dot(k1.rotation.normalize());
+            let angle = 2. * cos.abs().min(1.).acos();
+            let steps = (angle * 8. / std::f64::consts::PI).ceil().max(1.);
+            // Distance of the object to the center of the rotation, and change of size
+            let (mut rad, mut grow) = (0_f64, 0_f64);
+            for &p in &corners {
+                rad = rad.max((p * k0.scale).len()).max((p * k1.scale).len());
+                grow = grow.max((p * (k1.scale - k0.scale)).len());
+            }
+            // Error of the linear interpolation of a point between two steps:
+            // h^2 / 8 * |second derivative| with h = 1 / steps
+            pad = pad.max((angle * angle * rad + 2. * angle * grow) / (8. * steps * steps));
+            for s in 1..=steps as usize {
+                res = res.union(at(&k0.between(k1, s as f64 / steps)));
+            }
+        }
+        Some(res.pad(pad))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_follow_keyframes() {
+        let obj = Sphere {
+            center: Vec3(3., 0., 0.),
+            radius: 1.,
+            texture: Texture::Lambertian(RGB(0.5, 0.5, 0.5)),
+        }
+        .build();
+        let mut last = Keyframe::new(
+            1.,
+            Vec3(0., 2., 0.),
+            Quat::axis_angle(Vec3(0., 1., 0.), 170.),
+        );
+        last.scale = Vec3(2., 1., 1.);
+        let motion = Motion::keyframes(vec![
+            Keyframe::new(0., Vec3(0., 0., 0.), Quat::identity()),
+            last,
+        ]);
+        let moving = Moving {
+            obj: obj.clone(),
+            motion: motion.clone(),
+        }
+        .build();
+        let b = moving.bounds().unwrap();
+        for i in 0..=1000 {
+            let t = motion.at(i as f64 / 1000.);
+            let s = obj.bounds().unwrap().transform(&t.matrix);
+            assert!(b.contains(s.min) && b.contains(s.max));
+        }
+        // The sphere sweeps a half circle towards -z
+        assert!(b.min.2 < -5. && b.max.2 < 1.5);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::internal::*;
+
+/// Bicubic Bézier surface, rendered as a mesh
+///
+/// The patch is not a closed surface, so it should not be used in intersections
+/// or differences.
+#[derive(Clone)]
+pub struct BezierPatch {
+    /// `control[i][j]` is the j-th point along u of the i-th row along v
+    pub control: [[Vec3; 4]; 4],
+    /// Maximum distance between the surface and the triangles that approximate it
+    pub tolerance: f64,
+    pub texture: Texture,
+}
+
+impl BezierPatch {
+    /// Points of the curve along u that has parameter `v`
+    fn row(&self, v: f64) -> [Vec3; 4] {
+        let c = &self.control;
+        let mut res = [Vec3(0., 0., 0.); 4];
+        for (j, r) in res.iter_mut().enumerate() {
+            *r = spline::bezier(&[c[0][j], c[1][j], c[2][j], c[3][j]], v);
+        }
+        res
+    }
+
+    /// Points of the curve along v that has parameter `u`
+    fn column(&self, u: f64) -> [Vec3; 4] {
+        let c = &self.control;
+        [
+            spline::bezier(&c[0], u),
+            spline::bezier(&c[1], u),
+            spline::bezier(&c[2], u),
+            spline::bezier(&c[3], u),
+        ]
+    }
+
+    pub fn point(&self, u: f64, v: f64) -> Vec3 {
+        spline::bezier(&self.row(v), u)
+    }
+
+    /// Normal, oriented as the cross product of the derivatives along u and v
+    pub fn normal(&self, u: f64, v: f64) -> Vec3 {
+        let du = spline::bezier_tangent(&self.row(v), u);
+        let dv = spline::bezier_tangent(&self.column(u), v);
+        let n = du.cross(dv);
+        if n.len() > EPSILON {
+            n
+        } else {
+            // Degenerate corner: take the normal slightly towards the center
+            let nudge = |x: f64| x + (0.5 - x) * 0.001;
+            let du = spline::bezier_tangent(&self.row(nudge(v)), nudge(u));
+            let dv = spline::bezier_tangent(&self.column(nudge(u)), nudge(v));
+            du.cross(dv)
+        }
+    }
+
+    /// Approximate the patch by a grid of triangles, with a number of subdivisions
+    /// along each direction that depends on how curved the patch is
+    pub fn tessellate(&self) -> Mesh {
+        let c = &self.control;
+        let mut nu = 1;
+        let mut nv = 1;
+        for (i, row) in c.iter().enumerate() {
+            nu = nu.max(spline::flattening(row, self.tolerance));
+            nv = nv.max(spline::flattening(
+                &[c[0][i], c[1][i], c[2][i], c[3][i]],
+                self.tolerance,
+            ));
+        }
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        for i in 0..=nv {
+            for j in 0..=nu {
+                let (u, v) = (j as f64 / nu as f64, i as f64 / nv as f64);
+                vertices.push(self.point(u, v));
+                normals.push(self.normal(u, v).unit());
+            }
+        }
+        let mut faces = Vec::new();
+        for i in 0..nv {
+            for j in 0..nu {
+                let k = i * (nu + 1) + j;
+                faces.push([k, k + 1, k + nu + 2]);
+                faces.push([k, k + nu + 2, k + nu + 1]);
+            }
+        }
+        Mesh {
+            vertices,
+            normals,
+            faces,
+            texture: self.texture,
+        }
+    }
+
+    pub fn build(self) -> Primitive {
+        self.tessellate().build()
+    }
+}
@@ -0,0 +1,96 @@
+/// Real roots of `a*x^2 + b*x + c`
+pub fn quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < f64::EPSILON {
+        return if b.abs() < f64::EPSILON {
+            Vec::new()
+        } else {
+            vec![-c / b]
+        };
+    }
+    let det = b * b - 4. * a * c;
+    if det < 0. {
+        Vec::new()
+    } else {
+        // Avoid catastrophic cancellation by never subtracting numbers of the same sign
+        let q = -0.5 * (b + b.signum() * det.sqrt());
+        if q == 0. {
+            vec![0.]
+        } else {
+            vec![q / a, c / q]
+        }
+    }
+}
+
+/// Real roots of `x^3 + a*x^2 + b*x + c`
+pub fn cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let a3 = a / 3.;
+    // Depressed form y^3 + p*y + q with x = y - a/3
+    let p = b - a * a3;
+    let q = 2. * a3.powi(3) - a3 * b + c;
+    let det = (q / 2.).powi(2) + (p / 3.).powi(3);
+    let roots = if det > 0. {
+        let s = det.sqrt();
+        vec![(-q / 2. + s).cbrt() + (-q / 2. - s).cbrt()]
+    } else if p.abs() < f64::EPSILON {
+        vec![0.]
+    } else {
+        // Three real roots: trigonometric method
+        let m = 2. * (-p / 3.).sqrt();
+        let theta = (3. * q / (p * m)).clamp(-1., 1.).acos() / 3.;
+        let tau = 2. * std::f64::consts::PI / 3.;
+        vec![
+            m * theta.cos(),
+            m * (theta - tau).cos(),
+            m * (theta - 2. * tau).cos(),
+        ]
+    };
+    roots.into_iter().map(|y| y - a3).collect()
+}
+
+/// Real roots of `x^4 + a*x^3 + b*x^2 + c*x + d`, using
+/// [Ferrari's method](https://en.wikipedia.org/wiki/Quartic_function#Ferrari's_solution).
+///
+/// Closed forms lose a lot of precision, so each root is refined with a few
+/// steps of Newton's method.
+pub fn quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let a4 = a / 4.;
+    // Depressed form y^4 + p*y^2 + q*y + r with x = y - a/4
+    let p = b - 6. * a4 * a4;
+    let q = c - 2. * b * a4 + 8. * a4.powi(3);
+    let r = d - c * a4 + b * a4 * a4 - 3. * a4.powi(4);
+    let mut roots = Vec::new();
+    if q.abs() < 1e-12 {
+        // Biquadratic
+        for z in quadratic(1., p, r) {
+            if z >= 0. {
+                roots.push(z.sqrt());
+                roots.push(-z.sqrt());
+            }
+        }
+    } else {
+        // Resolvent cubic 8m^3 + 8p*m^2 + (2p^2 - 8r)*m - q^2, whose largest root is positive
+        let m = cubic(p, p * p / 4. - r, -q * q / 8.)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m > 0. {
+            let s = (2. * m).sqrt();
+            roots.extend(quadratic(1., -s, p / 2. + m + q / (2. * s)));
+            roots.extend(quadratic(1., s, p / 2. + m - q / (2. * s)));
+        }
+    }
+    roots
+        .into_iter()
+        .map(|y| {
+            let mut x = y - a4;
+            for _ in 0..3 {
+                let f = (((x + a) * x + b) * x + c) * x + d;
+                let df = ((4. * x + 3. * a) * x + 2. * b) * x + c;
+                if df.abs() < f64::EPSILON {
+                    break;
+                }
+                x -= f / df;
+            }
+            x
+        })
+        .collect()
+}
@@ -63,6 +63,28 @@ impl Hit for Sphere {
     fn inside(&self, pos: Vec3) -> bool {
         (pos - self.center).len() < self.radius
     }
+
+    fn bounds(&self) -> Option<Aabb> {
+        let r = Vec3(self.radius, self.radius, self.radius);
+        Some(Aabb::around(&[self.center - r, self.center + r]))
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let oc = r.orig - self.center;
+        let a = r.dir.dot_self();
+        let b = oc.dot(r.dir);
+        let c = oc.dot_self() - self.radius.powi(2);
+        let discriminant = b.powi(2) - a * c;
+        if discriminant <= EPSILON {
+            return Some(Vec::new());
+        }
+        let sqdet = discriminant.sqrt();
+        let crossing = |t| Crossing::new(t, r.project(t) - self.center, self.texture);
+        Some(vec![Span::new(
+            crossing((-b - sqdet) / a),
+            crossing((-b + sqdet) / a),
+        )])
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -132,6 +154,10 @@ impl Hit for Triangle {
 
     auto_texture! {}
     empty_object! {}
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::around(&[self.a, self.a + self.u, self.a + self.v]))
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -167,6 +193,11 @@ impl Hit for Parallelogram {
 
     auto_texture! {}
     empty_object! {}
+
+    fn bounds(&self) -> Option<Aabb> {
+        let (a, u, v) = (self.a, self.u, self.v);
+        Some(Aabb::around(&[a, a + u, a + v, a + u + v]))
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -273,7 +304,32 @@ impl Hit for RhomboidObject {
     }
 
     fn inside(&self, pos: Vec3) -> bool {
-        Interaction::bidir_hit(self, pos, Vec3(0.0, 1.0, 0.0))
+        // Coordinates of the point in the basis of the edges, using the dual basis
+        let (a, u, v, w) = (self.0[0].a, self.0[0].u, self.0[0].v, self.0[1].v);
+        let det = u.dot(v.cross(w));
+        let p = pos - a;
+        [v.cross(w), w.cross(u), u.cross(v)].iter().all(|n| {
+            let x = p.dot(*n) / det;
+            0. < x && x < 1.
+        })
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.0[0]
+            .bounds()
+            .zip(self.0[4].bounds())
+            .map(|(a, b)| a.union(b))
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        // Intersection of the three slabs between opposite faces
+        let mut spans = vec![Span::everywhere(self.0[0].texture)];
+        for &(i, j) in &[(0, 4), (1, 5), (2, 3)] {
+            let (f, g) = (&self.0[i], &self.0[j]);
+            let slab = Span::slab(r, f.u.cross(f.v), f.a, g.a, f.texture);
+            spans = Span::intersection(&spans, &slab);
+        }
+        Some(spans)
     }
 }
 
@@ -300,8 +356,8 @@ impl Hit for EmptyCylinder {
         let aoxab = ao.cross(ab);
         let vxab = ray.dir.cross(ab);
         let ab2 = ab.dot_self();
-        let a = 2. * vxab.dot_self();
-        let b = 2.0 * vxab.dot(aoxab);
+        let a = vxab.dot_self();
+        let b = vxab.dot(aoxab);
         let c = aoxab.dot_self() - self.radius.powi(2) * ab2;
 
         let det = b.powi(2) - a * c;
@@ -339,6 +395,16 @@ impl Hit for EmptyCylinder {
 
     auto_texture! {}
     empty_object! {}
+
+    fn bounds(&self) -> Option<Aabb> {
+        let r = Vec3(self.radius, self.radius, self.radius);
+        Some(Aabb::around(&[
+            self.center1 - r,
+            self.center1 + r,
+            self.center2 - r,
+            self.center2 + r,
+        ]))
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -375,6 +441,11 @@ impl Hit for Disc {
 
     auto_texture! {}
     empty_object! {}
+
+    fn bounds(&self) -> Option<Aabb> {
+        let r = Vec3(self.radius, self.radius, self.radius);
+        Some(Aabb::around(&[self.center - r, self.center + r]))
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -434,7 +505,51 @@ impl Hit for CylinderObject {
     }
 
     fn inside(&self, pos: Vec3) -> bool {
-        Interaction::bidir_hit(self, pos, self.cap1.normal)
+        let ab = self.side.center2 - self.side.center1;
+        let p = pos - self.side.center1;
+        let h = p.dot(ab) / ab.dot_self();
+        0. < h && h < 1. && (p - ab * h).len() < self.side.radius
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.side.bounds()
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let side = &self.side;
+        let ab = side.center2 - side.center1;
+        let udir = ab.unit();
+        let ao = r.orig - side.center1;
+        let aoxab = ao.cross(ab);
+        let vxab = r.dir.cross(ab);
+        let a = vxab.dot_self();
+        let b = vxab.dot(aoxab);
+        let c = aoxab.dot_self() - side.radius.powi(2) * ab.dot_self();
+        let tube = if a < EPSILON {
+            // Parallel to the axis
+            if c < 0. {
+                vec![Span::everywhere(side.texture)]
+            } else {
+                Vec::new()
+            }
+        } else {
+            let det = b.powi(2) - a * c;
+            if det < EPSILON {
+                return Some(Vec::new());
+            }
+            let crossing = |t| {
+                let u = r.project(t) - side.center1;
+                Crossing::new(t, u - udir * u.dot(udir), side.texture)
+            };
+            let sqdet = det.sqrt();
+            vec![Span::new(
+                crossing(-(b + sqdet) / a),
+                crossing(-(b - sqdet) / a),
+            )]
+        };
+        let (cap1, cap2) = (&self.cap1, &self.cap2);
+        let caps = Span::slab(r, cap1.normal, cap1.center, cap2.center, cap1.texture);
+        Some(Span::intersection(&tube, &caps))
     }
 }
 
@@ -502,6 +617,17 @@ impl Hit for EmptyCone {
 
     auto_texture! {}
     empty_object! {}
+
+    fn bounds(&self) -> Option<Aabb> {
+        let dir = self.dir.unit();
+        let disc = |d: f64| {
+            let r = (d * self.angle.tan()).abs();
+            let r = Vec3(r, r, r);
+            let c = self.orig + dir * d;
+            Aabb::around(&[c - r, c + r])
+        };
+        Some(disc(self.begin).union(disc(self.end)))
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -566,8 +692,259 @@ impl Hit for ConeObject {
     }
 
     fn inside(&self, pos: Vec3) -> bool {
-        let u = (pos - self.side.orig).unit();
-        let v = u - self.side.dir * u.dot(self.side.dir);
-        Interaction::bidir_hit(self, pos, v.cross(self.side.dir))
+        let dir = self.side.dir.unit();
+        let p = pos - self.side.orig;
+        let h = p.dot(dir);
+        self.side.begin < h
+            && h < self.side.end
+            && (p - dir * h).len() < h.abs() * self.side.angle.tan()
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.side.bounds()
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let side = &self.side;
+        if side.begin < 0. {
+            // Both halves of the double cone would be involved
+            return None;
+        }
+        let theta = side.dir.unit();
+        let k = side.angle.tan().powi(2) + 1.;
+        let diff = r.orig - side.orig;
+        let a = r.dir.dot_self() - k * r.dir.dot(theta).powi(2);
+        let b = 2. * (r.dir.dot(diff) - k * r.dir.dot(theta) * diff.dot(theta));
+        let c = diff.dot_self() - k * diff.dot(theta).powi(2);
+        if a.abs() < EPSILON {
+            return None;
+        }
+        let det = b.powi(2) - 4. * a * c;
+        let crossing = |t| {
+            let u = r.project(t) - side.orig;
+            Crossing::new(t, u.cross(u.cross(side.dir)), side.texture)
+        };
+        // Portions of the line inside of the double cone
+        let double = if det < EPSILON {
+            if a > 0. {
+                Vec::new()
+            } else {
+                vec![Span::everywhere(side.texture)]
+            }
+        } else {
+            let t1 = -(b + det.sqrt() * a.signum()) / (2. * a);
+            let t2 = -(b - det.sqrt() * a.signum()) / (2. * a);
+            if a > 0. {
+                vec![Span::new(crossing(t1), crossing(t2))]
+            } else {
+                let all = Span::everywhere(side.texture);
+                vec![
+                    Span::new(all.enter, crossing(t1)),
+                    Span::new(crossing(t2), all.exit),
+                ]
+            }
+        };
+        let (cap1, cap2) = (&self.cap1, &self.cap2);
+        let caps = Span::slab(r, cap1.normal, cap1.center, cap2.center, cap1.texture);
+        Some(Span::intersection(&double, &caps))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Torus {
+    pub center: Vec3,
+    /// Normal to the plane of the ring
+    pub axis: Vec3,
+    /// Distance from the center to the middle of the tube
+    pub major: f64,
+    /// Radius of the tube
+    pub minor: f64,
+    pub texture: Texture,
+}
+
+impl Torus {
+    pub fn build(self) -> Primitive {
+        Primitive(Arc::new(Self {
+            axis: self.axis.unit(),
+            ..self
+        }))
+    }
+
+    /// Outward normal at a point of the surface (relative to the center)
+    fn normal(&self, p: Vec3) -> Vec3 {
+        let flat = p - self.axis * p.dot(self.axis);
+        p - flat.unit() * self.major
+    }
+
+    /// All intersections of the line of the ray with the surface, in any order
+    fn roots(&self, r: &Ray) -> Vec<f64> {
+        // The quartic is badly conditioned when the ray starts far away from the torus,
+        // so it is solved for a unit direction starting from the point of the ray that
+        // is closest to the center.
+        let scale = r.dir.len();
+        let dir = r.dir / scale;
+        let t0 = (self.center - r.orig).dot(dir);
+        let o = r.orig + dir * t0 - self.center;
+        let (big, small) = (self.major.powi(2), self.minor.powi(2));
+        let od = o.dot(dir);
+        let oo = o.dot_self();
+        let on = o.dot(self.axis);
+        let dn = dir.dot(self.axis);
+        let k = oo + big - small;
+        poly::quartic(
+            4. * od,
+            4. * od * od + 2. * k - 4. * big * (1. - dn * dn),
+            4. * od * k - 8. * big * (od - on * dn),
+            k * k - 4. * big * (oo - on * on),
+        )
+        .into_iter()
+        .map(|s| (s + t0) / scale)
+        .collect()
+    }
+}
+
+impl Hit for Torus {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let mut rec = HitRecord::Blank;
+        for temp in self.roots(r) {
+            if EPSILON < temp {
+                let pos = r.project(temp);
+                rec.compare(HitRecord::make(
+                    temp,
+                    pos,
+                    self.normal(pos - self.center),
+                    self.texture,
+                ));
+            }
+        }
+        rec
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let mut roots = self.roots(r);
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let crossings = roots
+            .into_iter()
+            .map(|t| Crossing::new(t, self.normal(r.project(t) - self.center), self.texture))
+            .collect::<Vec<_>>();
+        // A tangent ray may have lost one of its roots
+        Span::pair(&crossings)
+    }
+
+    auto_texture! {}
+
+    fn inside(&self, pos: Vec3) -> bool {
+        let p = pos - self.center;
+        let h = p.dot(self.axis);
+        let d = (p - self.axis * h).len() - self.major;
+        d * d + h * h < self.minor.powi(2)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        let r = self.major + self.minor;
+        let r = Vec3(r, r, r);
+        Some(Aabb::around(&[self.center - r, self.center + r]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_vec(rng: &mut StdRng, size: f64) -> Vec3 {
+        Vec3(
+            rng.gen_range(-size, size),
+            rng.gen_range(-size, size),
+            rng.gen_range(-size, size),
+        )
+    }
+
+    /// A point is inside of a closed surface if a ray that starts there crosses the
+    /// surface an odd number of times
+    fn parity(obj: &dyn Hit, pos: Vec3, dir: Vec3) -> bool {
+        let mut ray = Ray::new(pos, dir);
+        let mut inside = false;
+        while let HitRecord::Hit(h) = obj.hit(&ray) {
+            inside = !inside;
+            ray.orig = h.pos + dir * EPSILON;
+        }
+        inside
+    }
+
+    /// Compare `inside` with the parity test on points around the object, and check
+    /// that both cases happen
+    fn check_inside(obj: Primitive, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let b = obj.bounds().unwrap().pad(0.1);
+        let mut count = 0;
+        for _ in 0..10000 {
+            let pos = b.min
+                + Vec3(
+                    rng.gen_range(0., b.size().0),
+                    rng.gen_range(0., b.size().1),
+                    rng.gen_range(0., b.size().2),
+                );
+            let dir = random_vec(&mut rng, 1.);
+            let inside = obj.inside(pos);
+            assert_eq!(inside, parity(&*obj.0, pos, dir));
+            count += inside as usize;
+        }
+        assert!(0 < count && count < 10000);
+    }
+
+    fn texture() -> Texture {
+        Texture::Lambertian(RGB(0.5, 0.5, 0.5))
+    }
+
+    #[test]
+    fn rhomboid_inside() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for i in 0..10 {
+            let obj = Rhomboid {
+                a: random_vec(&mut rng, 1.),
+                u: random_vec(&mut rng, 1.5),
+                v: random_vec(&mut rng, 1.5),
+                w: random_vec(&mut rng, 1.5),
+                texture: texture(),
+            }
+            .build();
+            check_inside(obj, i);
+        }
+    }
+
+    #[test]
+    fn cylinder_inside() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for i in 0..10 {
+            let center1 = random_vec(&mut rng, 1.);
+            let obj = Cylinder {
+                center1,
+                center2: center1 + random_vec(&mut rng, 1.5),
+                radius: rng.gen_range(0.3, 1.),
+                texture: texture(),
+            }
+            .build();
+            check_inside(obj, i);
+        }
+    }
+
+    #[test]
+    fn cone_inside() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for i in 0..10 {
+            let begin = rng.gen_range(0., 1.);
+            let obj = Cone {
+                orig: random_vec(&mut rng, 1.),
+                dir: random_vec(&mut rng, 1.),
+                angle: rng.gen_range(0.2, 0.8),
+                begin,
+                end: begin + rng.gen_range(0.5, 2.),
+                texture: texture(),
+            }
+            .build();
+            check_inside(obj, i);
+        }
     }
 }
@@ -0,0 +1,255 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// General surface of degree 2
+///
+/// Zeros of `a*x² + b*y² + c*z² + d*x*y + e*x*z + f*y*z + g*x + h*y + i*z + j`,
+/// with the coefficients given in this order.
+/// The inside of the object is where this expression is negative.
+#[derive(Clone, Copy)]
+pub struct Quadric {
+    pub coeffs: [f64; 10],
+    /// Only the part of the surface (and of the volume) inside this box is kept.
+    ///
+    /// The faces of the box are not part of the surface, the result is thus open
+    /// where the box cuts through the volume.
+    pub clip: Option<Aabb>,
+    pub texture: Texture,
+}
+
+impl Quadric {
+    pub fn build(self) -> Primitive {
+        Primitive(Arc::new(self))
+    }
+
+    /// Express the equation of the quadric in an orthonormal frame centered on `center`.
+    ///
+    /// In this frame, the equation is `sum(square[k] * x_k²) + sum(linear[k] * x_k) + constant`.
+    pub fn from_frame(
+        center: Vec3,
+        frame: [Vec3; 3],
+        square: [f64; 3],
+        linear: [f64; 3],
+        constant: f64,
+        clip: Option<Aabb>,
+        texture: Texture,
+    ) -> Self {
+        // Quadratic part is sum(s_k * e_k * e_k^T), linear part is sum(l_k * e_k)
+        let mut m = [[0.0; 3]; 3];
+        let mut l = Vec3(0.0, 0.0, 0.0);
+        for k in 0..3 {
+            let e = frame[k].unit();
+            let e = [e.0, e.1, e.2];
+            for (i, row) in m.iter_mut().enumerate() {
+                for (j, cell) in row.iter_mut().enumerate() {
+                    *cell += square[k] * e[i] * e[j];
+                }
+            }
+            l += frame[k].unit() * linear[k];
+        }
+        // Translate: (p-c)^T M (p-c) + l.(p-c) = p^T M p + (l - 2Mc).p + c^T M c - l.c
+        let c = [center.0, center.1, center.2];
+        let mc = Vec3(
+            m[0][0] * c[0] + m[0][1] * c[1] + m[0][2] * c[2],
+            m[1][0] * c[0] + m[1][1] * c[1] + m[1][2] * c[2],
+            m[2][0] * c[0] + m[2][1] * c[1] + m[2][2] * c[2],
+        );
+        let lin = l - mc * 2.;
+        Self {
+            coeffs: [
+                m[0][0],
+                m[1][1],
+                m[2][2],
+                2. * m[0][1],
+                2. * m[0][2],
+                2. * m[1][2],
+                lin.0,
+                lin.1,
+                lin.2,
+                center.dot(mc) - l.dot(center) + constant,
+            ],
+            clip,
+            texture,
+        }
+    }
+
+    /// Value of the equation at a point
+    pub fn eval(&self, p: Vec3) -> f64 {
+        let [a, b, c, d, e, f, g, h, i, j] = self.coeffs;
+        let Vec3(x, y, z) = p;
+        a * x * x
+            + b * y * y
+            + c * z * z
+            + d * x * y
+            + e * x * z
+            + f * y * z
+            + g * x
+            + h * y
+            + i * z
+            + j
+    }
+
+    /// Gradient of the equation, normal to the surface
+    pub fn gradient(&self, p: Vec3) -> Vec3 {
+        let [a, b, c, d, e, f, g, h, i, _] = self.coeffs;
+        let Vec3(x, y, z) = p;
+        Vec3(
+            2. * a * x + d * y + e * z + g,
+            2. * b * y + d * x + f * z + h,
+            2. * c * z + e * x + f * y + i,
+        )
+    }
+
+    fn in_clip(&self, p: Vec3) -> bool {
+        match self.clip {
+            None => true,
+            Some(b) => b.contains(p),
+        }
+    }
+}
+
+impl Hit for Quadric {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let [a, b, c, d, e, f, g, h, i, _] = self.coeffs;
+        let Vec3(ox, oy, oz) = r.orig;
+        let Vec3(dx, dy, dz) = r.dir;
+        let qa = a * dx * dx + b * dy * dy + c * dz * dz + d * dx * dy + e * dx * dz + f * dy * dz;
+        let qb = 2. * (a * ox * dx + b * oy * dy + c * oz * dz)
+            + d * (ox * dy + oy * dx)
+            + e * (ox * dz + oz * dx)
+            + f * (oy * dz + oz * dy)
+            + g * dx
+            + h * dy
+            + i * dz;
+        let qc = self.eval(r.orig);
+        let mut rec = HitRecord::Blank;
+        for temp in poly::quadratic(qa, qb, qc) {
+            if EPSILON < temp {
+                let pos = r.project(temp);
+                if self.in_clip(pos) {
+                    rec.compare(HitRecord::make(temp, pos, self.gradient(pos), self.texture));
+                }
+            }
+        }
+        rec
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.in_clip(pos) && self.eval(pos) < 0.
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.clip
+    }
+}
+
+const AXES: [Vec3; 3] = [
+    Vec3(1.0, 0.0, 0.0),
+    Vec3(0.0, 1.0, 0.0),
+    Vec3(0.0, 0.0, 1.0),
+];
+
+/// Axis-aligned ellipsoid
+#[derive(Clone, Copy)]
+pub struct Ellipsoid {
+    pub center: Vec3,
+    /// Semi-axes along x, y and z
+    pub radii: Vec3,
+    pub texture: Texture,
+}
+
+impl Ellipsoid {
+    pub fn build(self) -> Primitive {
+        let Vec3(a, b, c) = self.radii;
+        // The clip only bounds the solid, it does not cut through it
+        let margin = self.radii * EPSILON;
+        Quadric::from_frame(
+            self.center,
+            AXES,
+            [a.powi(-2), b.powi(-2), c.powi(-2)],
+            [0.0; 3],
+            -1.,
+            Some(Aabb {
+                min: self.center - self.radii - margin,
+                max: self.center + self.radii + margin,
+            }),
+            self.texture,
+        )
+        .build()
+    }
+}
+
+/// Paraboloid of revolution, opening upwards
+#[derive(Clone, Copy)]
+pub struct Paraboloid {
+    /// Lowest point
+    pub vertex: Vec3,
+    /// Radius of the opening
+    pub radius: f64,
+    /// Distance from the vertex to the opening
+    pub height: f64,
+    pub texture: Texture,
+}
+
+impl Paraboloid {
+    pub fn build(self) -> Primitive {
+        let r = self.radius;
+        let margin = Vec3(r, 0.0, r) * EPSILON;
+        Quadric::from_frame(
+            self.vertex,
+            AXES,
+            [1. / (r * r), 0.0, 1. / (r * r)],
+            [0.0, -1. / self.height, 0.0],
+            0.0,
+            Some(Aabb {
+                min: self.vertex - Vec3(r, 0.0, r) - margin,
+                max: self.vertex + Vec3(r, self.height, r) + margin,
+            }),
+            self.texture,
+        )
+        .build()
+    }
+}
+
+/// Hyperboloid of revolution around a vertical axis
+///
+/// With one sheet, the inside is the region around the axis.
+/// With two sheets, the inside is the region within each of the two bowls.
+#[derive(Clone, Copy)]
+pub struct Hyperboloid {
+    pub center: Vec3,
+    /// Radius of the waist (one sheet), or horizontal scale (two sheets)
+    pub radius: f64,
+    /// Vertical scale: the asymptotic cone has a slope of `radius / scale`
+    pub scale: f64,
+    /// Only the part between `center.1 - height` and `center.1 + height` is kept
+    pub height: f64,
+    /// Two sheets instead of one
+    pub two_sheets: bool,
+    pub texture: Texture,
+}
+
+impl Hyperboloid {
+    pub fn build(self) -> Primitive {
+        let (a, c, h) = (self.radius, self.scale, self.height);
+        let constant = if self.two_sheets { 1. } else { -1. };
+        let rmax = a * (1. + (h / c).powi(2)).sqrt() * (1. + EPSILON);
+        Quadric::from_frame(
+            self.center,
+            AXES,
+            [1. / (a * a), -1. / (c * c), 1. / (a * a)],
+            [0.0; 3],
+            constant,
+            Some(Aabb {
+                min: self.center - Vec3(rmax, h, rmax),
+                max: self.center + Vec3(rmax, h, rmax),
+            }),
+            self.texture,
+        )
+        .build()
+    }
+}
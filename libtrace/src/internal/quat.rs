@@ -0,0 +1,212 @@
+use crate::internal::*;
+use std::ops;
+
+/// Rotation represented as a unit quaternion `w + xi + yj + zk`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quat {
+    pub fn identity() -> Self {
+        Self {
+            w: 1.,
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        }
+    }
+
+    /// Rotation of `angle` degrees around `axis`, counterclockwise when the axis
+    /// points towards the viewer
+    pub fn axis_angle(axis: Vec3, angle: f64) -> Self {
+        let a = axis.unit();
+        let (s, c) = (angle.to_radians() / 2.).sin_cos();
+        Self {
+            w: c,
+            x: a.0 * s,
+            y: a.1 * s,
+            z: a.2 * s,
+        }
+    }
+
+    /// Rotation given by Euler angles in degrees: `roll` around z, then `pitch`
+    /// around x, then `yaw` around the vertical axis y
+    pub fn euler(yaw: f64, pitch: f64, roll: f64) -> Self {
+        Self::axis_angle(Vec3(0., 1., 0.), yaw)
+            * Self::axis_angle(Vec3(1., 0., 0.), pitch)
+            * Self::axis_angle(Vec3(0., 0., 1.), roll)
+    }
+
+    /// Axis and angle (in degrees) of the rotation
+    pub fn to_axis_angle(&self) -> (Vec3, f64) {
+        let q = self.normalize();
+        let s = (1. - q.w * q.w).max(0.).sqrt();
+        let angle = 2. * q.w.clamp(-1., 1.).acos().to_degrees();
+        if s < f64::EPSILON {
+            (Vec3(1., 0., 0.), 0.)
+        } else {
+            (Vec3(q.x / s, q.y / s, q.z / s), angle)
+        }
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        *self * (1. / self.norm())
+    }
+
+    pub fn dot(&self, other: Self) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Opposite rotation (for a unit quaternion)
+    pub fn conjugate(&self) -> Self {
+        Self {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.conjugate() * (1. / self.dot(*self))
+    }
+
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        // v + 2w (q × v) + 2 q × (q × v), with q the vector part
+        let q = Vec3(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.;
+        v + t * self.w + q.cross(t)
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from_columns(
+            self.rotate(Vec3(1., 0., 0.)),
+            self.rotate(Vec3(0., 1., 0.)),
+            self.rotate(Vec3(0., 0., 1.)),
+        )
+    }
+
+    /// Spherical linear interpolation: constant speed rotation from `self` (t = 0)
+    /// to `other` (t = 1) along the shortest path
+    pub fn slerp(&self, other: Self, t: f64) -> Self {
+        let mut cos = self.dot(other);
+        let other = if cos < 0. {
+            cos = -cos;
+            other * -1.
+        } else {
+            other
+        };
+        if cos > 0.9995 {
+            // Nearly identical rotations: a linear interpolation is precise enough
+            return (*self * (1. - t) + other * t).normalize();
+        }
+        let theta = cos.acos();
+        let sin = theta.sin();
+        *self * (((1. - t) * theta).sin() / sin) + other * ((t * theta).sin() / sin)
+    }
+}
+
+/// Composition: `(a * b)` applies `b` first, then `a`
+impl ops::Mul for Quat {
+    type Output = Self;
+
+    fn mul(self, o: Self) -> Self {
+        Self {
+            w: self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            x: self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            y: self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            z: self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        }
+    }
+}
+
+impl ops::Mul<f64> for Quat {
+    type Output = Self;
+
+    fn mul(self, k: f64) -> Self {
+        Self {
+            w: self.w * k,
+            x: self.x * k,
+            y: self.y * k,
+            z: self.z * k,
+        }
+    }
+}
+
+impl ops::Add for Quat {
+    type Output = Self;
+
+    fn add(self, o: Self) -> Self {
+        Self {
+            w: self.w + o.w,
+            x: self.x + o.x,
+            y: self.y + o.y,
+            z: self.z + o.z,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).len() < 1e-9
+    }
+
+    #[test]
+    fn same_as_matrix() {
+        let axis = Vec3(1., -2., 0.5);
+        let q = Quat::axis_angle(axis, 73.);
+        let m = Mat3::rotation(axis, 73.);
+        let v = Vec3(0.2, 3., -1.);
+        assert!(close(q.rotate(v), m.apply(v)));
+        assert!(close(q.to_mat3().apply(v), m.apply(v)));
+    }
+
+    #[test]
+    fn composition_and_inverse() {
+        let a = Quat::axis_angle(Vec3(0., 1., 0.), 30.);
+        let b = Quat::axis_angle(Vec3(1., 0., 0.), 50.);
+        let v = Vec3(1., 2., 3.);
+        assert!(close((a * b).rotate(v), a.rotate(b.rotate(v))));
+        assert!(close((a * b).inverse().rotate((a * b).rotate(v)), v));
+    }
+
+    #[test]
+    fn euler_order() {
+        let q = Quat::euler(90., 0., 0.);
+        assert!(close(q.rotate(Vec3(0., 0., 1.)), Vec3(1., 0., 0.)));
+        let q = Quat::euler(0., 90., 0.);
+        assert!(close(q.rotate(Vec3(0., 1., 0.)), Vec3(0., 0., 1.)));
+        let q = Quat::euler(90., 90., 0.);
+        // Pitch is applied before yaw
+        assert!(close(q.rotate(Vec3(0., 1., 0.)), Vec3(1., 0., 0.)));
+    }
+
+    #[test]
+    fn axis_angle_roundtrip() {
+        let (axis, angle) = Quat::axis_angle(Vec3(0., 3., 4.), 40.).to_axis_angle();
+        assert!(close(axis, Vec3(0., 0.6, 0.8)));
+        assert!((angle - 40.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let a = Quat::identity();
+        let b = Quat::axis_angle(Vec3(0., 0., 1.), 90.);
+        let q = a.slerp(b, 0.5);
+        assert!(close(
+            q.rotate(Vec3(1., 0., 0.)),
+            Vec3(0.5f64.sqrt(), 0.5f64.sqrt(), 0.)
+        ));
+    }
+}
@@ -4,11 +4,26 @@ use crate::internal::*;
 pub struct Ray {
     pub orig: Vec3,
     pub dir: Vec3,
+    /// Instant at which the ray is cast, for moving objects
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(orig: Vec3, dir: Vec3) -> Self {
-        Self { orig, dir }
+        Self {
+            orig,
+            dir,
+            time: 0.,
+        }
+    }
+
+    /// Ray cast at the same instant as `self`, used for reflections and refractions
+    pub fn spawn(&self, orig: Vec3, dir: Vec3) -> Self {
+        Self {
+            orig,
+            dir,
+            time: self.time,
+        }
     }
 
     /// When `temp` describes the real numbers, `self.project(temp)`
@@ -0,0 +1,259 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Maximum number of steps of the sphere tracing
+const MAX_STEPS: usize = 512;
+/// Distance under which the surface is considered reached
+const HIT_DIST: f64 = 0.000_01;
+/// Distance after which an unbounded object is considered missed
+const MAX_DIST: f64 = 1000.;
+
+/// Signed distance function: negative inside the object, positive outside,
+/// and never bigger than the actual distance to the surface.
+///
+/// Objects described this way are rendered by
+/// [sphere tracing](https://iquilezles.org/articles/distfunctions/), which makes smooth
+/// blends between objects possible.
+#[derive(Clone)]
+pub enum Sdf {
+    Sphere {
+        center: Vec3,
+        radius: f64,
+    },
+    /// Axis-aligned box
+    Cuboid {
+        center: Vec3,
+        /// Half of the size along each axis
+        half: Vec3,
+    },
+    /// Axis-aligned box with rounded edges
+    RoundCuboid {
+        center: Vec3,
+        half: Vec3,
+        /// Radius of the edges
+        radius: f64,
+    },
+    /// Cylinder with two half-spheres at the ends
+    Capsule {
+        a: Vec3,
+        b: Vec3,
+        radius: f64,
+    },
+    /// Horizontal torus
+    Torus {
+        center: Vec3,
+        major: f64,
+        minor: f64,
+    },
+    /// Union where the junction is rounded over a distance `k`
+    SmoothUnion(Box<Sdf>, Box<Sdf>, f64),
+    /// First object minus the second one, rounded over a distance `k`
+    SmoothSubtract(Box<Sdf>, Box<Sdf>, f64),
+    /// Intersection rounded over a distance `k`
+    SmoothIntersect(Box<Sdf>, Box<Sdf>, f64),
+    /// Infinite repetition of the object with the given period along each axis
+    ///
+    /// A period of 0 means no repetition along this axis.
+    /// The object should fit in one period for the distance to remain correct.
+    Repeat(Box<Sdf>, Vec3),
+}
+
+/// Linear interpolation
+fn mix(a: f64, b: f64, h: f64) -> f64 {
+    a * (1. - h) + b * h
+}
+
+fn clamp01(x: f64) -> f64 {
+    x.clamp(0., 1.)
+}
+
+/// Remainder around zero: the result is between -period/2 and period/2
+fn centered_mod(x: f64, period: f64) -> f64 {
+    if period == 0. {
+        x
+    } else {
+        x - period * (x / period).round()
+    }
+}
+
+impl Sdf {
+    pub fn smooth_union(self, other: Self, k: f64) -> Self {
+        Sdf::SmoothUnion(Box::new(self), Box::new(other), k)
+    }
+
+    pub fn smooth_subtract(self, other: Self, k: f64) -> Self {
+        Sdf::SmoothSubtract(Box::new(self), Box::new(other), k)
+    }
+
+    pub fn smooth_intersect(self, other: Self, k: f64) -> Self {
+        Sdf::SmoothIntersect(Box::new(self), Box::new(other), k)
+    }
+
+    pub fn repeat(self, period: Vec3) -> Self {
+        Sdf::Repeat(Box::new(self), period)
+    }
+
+    /// Signed distance from `p` to the surface
+    pub fn dist(&self, p: Vec3) -> f64 {
+        match self {
+            Sdf::Sphere { center, radius } => (p - *center).len() - radius,
+            Sdf::Cuboid { center, half } => {
+                let d = p - *center;
+                let q = Vec3(d.0.abs() - half.0, d.1.abs() - half.1, d.2.abs() - half.2);
+                let out = Vec3(q.0.max(0.), q.1.max(0.), q.2.max(0.)).len();
+                out + q.0.max(q.1).max(q.2).min(0.)
+            }
+            Sdf::RoundCuboid {
+                center,
+                half,
+                radius,
+            } => {
+                let r = Vec3(*radius, *radius, *radius);
+                Sdf::Cuboid {
+                    center: *center,
+                    half: *half - r,
+                }
+                .dist(p)
+                    - radius
+            }
+            Sdf::Capsule { a, b, radius } => {
+                let pa = p - *a;
+                let ba = *b - *a;
+                let h = clamp01(pa.dot(ba) / ba.dot_self());
+                (pa - ba * h).len() - radius
+            }
+            Sdf::Torus {
+                center,
+                major,
+                minor,
+            } => {
+                let d = p - *center;
+                let flat = (d.0.powi(2) + d.2.powi(2)).sqrt() - major;
+                (flat.powi(2) + d.1.powi(2)).sqrt() - minor
+            }
+            Sdf::SmoothUnion(a, b, k) => {
+                let (d1, d2) = (a.dist(p), b.dist(p));
+                let h = clamp01(0.5 + 0.5 * (d2 - d1) / k);
+                mix(d2, d1, h) - k * h * (1. - h)
+            }
+            Sdf::SmoothSubtract(a, b, k) => {
+                let (d1, d2) = (a.dist(p), b.dist(p));
+                let h = clamp01(0.5 - 0.5 * (d1 + d2) / k);
+                mix(d1, -d2, h) + k * h * (1. - h)
+            }
+            Sdf::SmoothIntersect(a, b, k) => {
+                let (d1, d2) = (a.dist(p), b.dist(p));
+                let h = clamp01(0.5 - 0.5 * (d2 - d1) / k);
+                mix(d2, d1, h) + k * h * (1. - h)
+            }
+            Sdf::Repeat(a, period) => a.dist(Vec3(
+                centered_mod(p.0, period.0),
+                centered_mod(p.1, period.1),
+                centered_mod(p.2, period.2),
+            )),
+        }
+    }
+
+    /// Box outside of which the distance is guaranteed to be positive, if there is one
+    pub fn bounds(&self) -> Option<Aabb> {
+        match self {
+            Sdf::Sphere { center, radius } => {
+                let r = Vec3(*radius, *radius, *radius);
+                Some(Aabb::around(&[*center - r, *center + r]))
+            }
+            Sdf::Cuboid { center, half } | Sdf::RoundCuboid { center, half, .. } => {
+                Some(Aabb::around(&[*center - *half, *center + *half]))
+            }
+            Sdf::Capsule { a, b, radius } => {
+                let r = Vec3(*radius, *radius, *radius);
+                Some(Aabb::around(&[*a - r, *a + r, *b - r, *b + r]))
+            }
+            Sdf::Torus {
+                center,
+                major,
+                minor,
+            } => {
+                let r = Vec3(major + minor, *minor, major + minor);
+                Some(Aabb::around(&[*center - r, *center + r]))
+            }
+            Sdf::SmoothUnion(a, b, k) => match (a.bounds(), b.bounds()) {
+                (Some(a), Some(b)) => Some(a.union(b).pad(*k)),
+                _ => None,
+            },
+            Sdf::SmoothSubtract(a, _, _) => a.bounds(),
+            Sdf::SmoothIntersect(a, b, _) => a.bounds().or_else(|| b.bounds()),
+            Sdf::Repeat(_, _) => None,
+        }
+    }
+
+    /// Normal to the surface, calculated from the gradient of the distance
+    pub fn gradient(&self, p: Vec3) -> Vec3 {
+        let h = HIT_DIST;
+        let dx = Vec3(h, 0., 0.);
+        let dy = Vec3(0., h, 0.);
+        let dz = Vec3(0., 0., h);
+        Vec3(
+            self.dist(p + dx) - self.dist(p - dx),
+            self.dist(p + dy) - self.dist(p - dy),
+            self.dist(p + dz) - self.dist(p - dz),
+        )
+    }
+
+    pub fn build(self, texture: Texture) -> Primitive {
+        let bounds = self.bounds().map(|b| b.pad(HIT_DIST * 10.));
+        Primitive(Arc::new(SdfObject {
+            sdf: self,
+            bounds,
+            texture,
+        }))
+    }
+}
+
+pub struct SdfObject {
+    sdf: Sdf,
+    bounds: Option<Aabb>,
+    texture: Texture,
+}
+
+impl Hit for SdfObject {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        let scale = r.dir.len();
+        // Work with distances rather than with multiples of `r.dir`
+        let (mut t, tmax) = match self.bounds {
+            None => (0., MAX_DIST),
+            Some(b) => match b.range(r) {
+                None => return HitRecord::Blank,
+                Some((lo, hi)) => (lo.max(0.) * scale, hi * scale),
+            },
+        };
+        let dir = r.dir / scale;
+        // Skip past the surface in case the ray starts on it
+        t = t.max(HIT_DIST * 10.);
+        // Rays that start inside the object march towards its exit
+        let sign = self.sdf.dist(r.orig + dir * t).signum();
+        for _ in 0..MAX_STEPS {
+            if t > tmax {
+                break;
+            }
+            let pos = r.orig + dir * t;
+            let d = self.sdf.dist(pos) * sign;
+            if d < HIT_DIST {
+                return HitRecord::make(t / scale, pos, self.sdf.gradient(pos), self.texture);
+            }
+            t += d;
+        }
+        HitRecord::Blank
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.sdf.dist(pos) < 0.
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+}
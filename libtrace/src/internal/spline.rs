@@ -0,0 +1,151 @@
+use crate::internal::*;
+
+/// Point of a uniform Catmull-Rom spline at position `t` (between 0 and 1) of the
+/// segment that goes from `p1` to `p2`
+pub fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f64) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.
+        + (p2 - p0) * t
+        + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * t2
+        + (-p0 + p1 * 3. - p2 * 3. + p3) * t3)
+        * 0.5
+}
+
+/// Sample a Catmull-Rom spline that goes through all of the points, with `n` samples
+/// per segment.
+///
+/// The end points are repeated so that the curve starts and ends exactly at the first
+/// and last points.
+pub fn catmull_rom_chain(pts: &[Vec3], n: usize) -> Vec<Vec3> {
+    if pts.len() < 2 {
+        return pts.to_vec();
+    }
+    let at = |i: isize| pts[i.max(0).min(pts.len() as isize - 1) as usize];
+    let mut res = Vec::new();
+    for i in 0..pts.len() as isize - 1 {
+        for k in 0..n {
+            let t = k as f64 / n as f64;
+            res.push(catmull_rom(at(i - 1), at(i), at(i + 1), at(i + 2), t));
+        }
+    }
+    res.push(pts[pts.len() - 1]);
+    res
+}
+
+/// Point of a cubic Bézier curve at position `t` (between 0 and 1)
+pub fn bezier(p: &[Vec3; 4], t: f64) -> Vec3 {
+    let s = 1. - t;
+    p[0] * (s * s * s) + p[1] * (3. * s * s * t) + p[2] * (3. * s * t * t) + p[3] * (t * t * t)
+}
+
+/// Derivative of a cubic Bézier curve with respect to `t`
+pub fn bezier_tangent(p: &[Vec3; 4], t: f64) -> Vec3 {
+    let s = 1. - t;
+    (p[1] - p[0]) * (3. * s * s) + (p[2] - p[1]) * (6. * s * t) + (p[3] - p[2]) * (3. * t * t)
+}
+
+/// Control points of the Bézier curve that is identical to the segment of a
+/// Catmull-Rom spline that goes from `p1` to `p2`
+pub fn catmull_rom_to_bezier(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3) -> [Vec3; 4] {
+    [p1, p1 + (p2 - p0) / 6., p2 - (p3 - p1) / 6., p2]
+}
+
+/// Number of straight segments needed to approximate a cubic Bézier curve
+/// with an error smaller than `tolerance`
+///
+/// The bound comes from the maximum of the second derivative, which is reached at
+/// one of the second differences of the control points.
+pub fn flattening(p: &[Vec3; 4], tolerance: f64) -> usize {
+    let m = (p[0] - p[1] * 2. + p[2])
+        .len()
+        .max((p[1] - p[2] * 2. + p[3]).len());
+    ((0.75 * m / tolerance).sqrt().ceil() as usize).max(1)
+}
+
+/// Number of samples per segment of the table used to travel at constant speed
+const ARC_SAMPLES: usize = 32;
+
+/// Chain of cubic Bézier segments that can be followed at constant speed
+#[derive(Clone)]
+pub struct Path {
+    segments: Vec<[Vec3; 4]>,
+    /// Length of the curve from its start to each sample
+    arc: Vec<f64>,
+}
+
+impl Path {
+    /// Curve through all the points, with the same tangents as a Catmull-Rom spline
+    pub fn catmull_rom(pts: &[Vec3]) -> Self {
+        if pts.len() < 2 {
+            panic!("A path needs at least two points");
+        }
+        let at = |i: isize| pts[i.max(0).min(pts.len() as isize - 1) as usize];
+        let segments = (0..pts.len() as isize - 1)
+            .map(|i| catmull_rom_to_bezier(at(i - 1), at(i), at(i + 1), at(i + 2)))
+            .collect();
+        Self::new(segments)
+    }
+
+    /// Curve made of Bézier segments, where `pts` has `3n+1` points and the last
+    /// point of each segment is the first point of the next one
+    pub fn bezier(pts: &[Vec3]) -> Self {
+        if pts.len() < 4 || pts.len() % 3 != 1 {
+            panic!("A Bézier path needs 3n+1 points, got {}", pts.len());
+        }
+        let segments = pts
+            .windows(4)
+            .step_by(3)
+            .map(|w| [w[0], w[1], w[2], w[3]])
+            .collect();
+        Self::new(segments)
+    }
+
+    fn new(segments: Vec<[Vec3; 4]>) -> Self {
+        let mut arc = vec![0.];
+        let mut prev = segments[0][0];
+        for seg in &segments {
+            for k in 1..=ARC_SAMPLES {
+                let p = bezier(seg, k as f64 / ARC_SAMPLES as f64);
+                arc.push(arc[arc.len() - 1] + (p - prev).len());
+                prev = p;
+            }
+        }
+        Self { segments, arc }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.arc[self.arc.len() - 1]
+    }
+
+    /// Segment and position in that segment of the point at fraction `s` (between 0 and 1)
+    /// of the length of the curve
+    fn locate(&self, s: f64) -> (usize, f64) {
+        let dist = s.clamp(0., 1.) * self.length();
+        let k = match self.arc.binary_search_by(|a| a.partial_cmp(&dist).unwrap()) {
+            Ok(k) => k,
+            Err(k) => k - 1,
+        }
+        .min(self.arc.len() - 2);
+        let frac = if self.arc[k + 1] > self.arc[k] {
+            (dist - self.arc[k]) / (self.arc[k + 1] - self.arc[k])
+        } else {
+            0.
+        };
+        let pos = (k as f64 + frac) / ARC_SAMPLES as f64;
+        let seg = (pos as usize).min(self.segments.len() - 1);
+        (seg, pos - seg as f64)
+    }
+
+    /// Point at fraction `s` of the length of the curve
+    pub fn at(&self, s: f64) -> Vec3 {
+        let (seg, t) = self.locate(s);
+        bezier(&self.segments[seg], t)
+    }
+
+    /// Direction of the curve at fraction `s` of its length (not normalized)
+    pub fn tangent(&self, s: f64) -> Vec3 {
+        let (seg, t) = self.locate(s);
+        bezier_tangent(&self.segments[seg], t)
+    }
+}
@@ -0,0 +1,364 @@
+use crate::internal::*;
+use crate::render::{render, Builder};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// How a track goes from one key to the next
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Keep the value of the key until the next one
+    Step,
+    /// Straight line, or shortest arc for rotations (slerp)
+    Linear,
+    /// Smooth curve whose Bézier control points are derived from the neighbouring keys
+    Bezier,
+}
+
+impl Interpolation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "step" => Some(Interpolation::Step),
+            "linear" | "slerp" => Some(Interpolation::Linear),
+            "bezier" => Some(Interpolation::Bezier),
+            _ => None,
+        }
+    }
+}
+
+/// Values that can be interpolated by a track
+pub trait Animatable: Copy {
+    fn mix(self, other: Self, t: f64) -> Self;
+
+    /// Point at `t` of the Bézier curve from `b` to `c` with the same tangents as the
+    /// Catmull-Rom spline through `a`, `b`, `c` and `d`
+    fn smooth(a: Self, b: Self, c: Self, d: Self, t: f64) -> Self;
+}
+
+macro_rules! animatable_linear {
+    ( $t:ty ) => {
+        impl Animatable for $t {
+            fn mix(self, other: Self, t: f64) -> Self {
+                self + (other - self) * t
+            }
+
+            fn smooth(a: Self, b: Self, c: Self, d: Self, t: f64) -> Self {
+                let c1 = b + (c - a) * (1. / 6.);
+                let c2 = c - (d - b) * (1. / 6.);
+                let s = 1. - t;
+                b * (s * s * s) + c1 * (3. * s * s * t) + c2 * (3. * s * t * t) + c * (t * t * t)
+            }
+        }
+    };
+}
+
+animatable_linear!(f64);
+animatable_linear!(Vec3);
+animatable_linear!(RGB);
+
+impl Animatable for Quat {
+    fn mix(self, other: Self, t: f64) -> Self {
+        self.slerp(other, t)
+    }
+
+    /// Slerp that eases in and out of the keys
+    fn smooth(_a: Self, b: Self, c: Self, _d: Self, t: f64) -> Self {
+        b.slerp(c, t * t * (3. - 2. * t))
+    }
+}
+
+/// Values of a variable at given instants
+#[derive(Clone)]
+pub struct Track<T: Animatable> {
+    /// Sorted by time, the interpolation applies until the next key
+    keys: Vec<(f64, T, Interpolation)>,
+}
+
+impl<T: Animatable> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key, replacing the one at the same instant if any
+    pub fn key(&mut self, time: f64, value: T, interp: Interpolation) {
+        self.keys.retain(|k| k.0 != time);
+        let idx = self.keys.iter().position(|k| k.0 > time);
+        let idx = idx.unwrap_or(self.keys.len());
+        self.keys.insert(idx, (time, value, interp));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Value at any instant, the first and last keys hold before and after the track
+    pub fn at(&self, time: f64) -> Option<T> {
+        let n = self.keys.len();
+        let next = self.keys.iter().position(|k| k.0 > time);
+        match next {
+            _ if n == 0 => None,
+            Some(0) => Some(self.keys[0].1),
+            None => Some(self.keys[n - 1].1),
+            Some(i) => {
+                let (t0, b, interp) = self.keys[i - 1];
+                let (t1, c, _) = self.keys[i];
+                let t = (time - t0) / (t1 - t0);
+                Some(match interp {
+                    Interpolation::Step => b,
+                    Interpolation::Linear => b.mix(c, t),
+                    Interpolation::Bezier => {
+                        let a = self.keys[i.max(2) - 2].1;
+                        let d = self.keys[(i + 1).min(n - 1)].1;
+                        T::smooth(a, b, c, d, t)
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Parameters of `Camera::new_relative` and of the lens
+#[derive(Clone, Copy)]
+pub struct CameraKey {
+    pub target: Vec3,
+    pub angle: f64,
+    pub rise: f64,
+    pub distance: f64,
+    pub tilt: f64,
+    pub aperture: f64,
+    /// Radius of the lens
+    pub lens: f64,
+}
+
+impl CameraKey {
+    /// focus: distance at which objects are sharp, `None` to focus on the target
+    pub fn camera(&self, ratio: f64, projection: Projection, focus: Option<f64>) -> Camera {
+        let mut cam = Camera::new_relative(
+            self.target,
+            self.angle,
+            self.rise,
+            self.distance,
+            self.tilt,
+            self.aperture,
+            ratio,
+            projection,
+        );
+        cam.set_lens(self.lens, focus);
+        cam
+    }
+}
+
+impl Animatable for CameraKey {
+    fn mix(self, other: Self, t: f64) -> Self {
+        Self {
+            target: self.target.mix(other.target, t),
+            angle: self.angle.mix(other.angle, t),
+            rise: self.rise.mix(other.rise, t),
+            distance: self.distance.mix(other.distance, t),
+            tilt: self.tilt.mix(other.tilt, t),
+            aperture: self.aperture.mix(other.aperture, t),
+            lens: self.lens.mix(other.lens, t),
+        }
+    }
+
+    fn smooth(a: Self, b: Self, c: Self, d: Self, t: f64) -> Self {
+        let f = |g: fn(&Self) -> f64| f64::smooth(g(&a), g(&b), g(&c), g(&d), t);
+        Self {
+            target: Vec3::smooth(a.target, b.target, c.target, d.target, t),
+            angle: f(|k| k.angle),
+            rise: f(|k| k.rise),
+            distance: f(|k| k.distance),
+            tilt: f(|k| k.tilt),
+            aperture: f(|k| k.aperture),
+            lens: f(|k| k.lens),
+        }
+    }
+}
+
+/// Object of the scene whose position and color change over time
+///
+/// Empty tracks leave the object as it was built.
+#[derive(Clone)]
+pub struct Animated {
+    pub obj: Composite,
+    pub translation: Track<Vec3>,
+    /// Rotation around the origin, applied after the scaling and before the translation
+    pub rotation: Track<Quat>,
+    pub scale: Track<Vec3>,
+    /// Replaces the color of all textures of the object
+    pub color: Track<RGB>,
+}
+
+impl Animated {
+    pub fn new(obj: Composite) -> Self {
+        Self {
+            obj,
+            translation: Track::new(),
+            rotation: Track::new(),
+            scale: Track::new(),
+            color: Track::new(),
+        }
+    }
+
+    fn keyframe(&self, time: f64) -> Keyframe {
+        Keyframe {
+            time,
+            translation: self.translation.at(time).unwrap_or(Vec3(0., 0., 0.)),
+            rotation: self.rotation.at(time).unwrap_or_else(Quat::identity),
+            scale: self.scale.at(time).unwrap_or(Vec3(1., 1., 1.)),
+        }
+    }
+
+    /// Object as it is seen while the shutter is open from `time` to `time + shutter`
+    pub fn at(&self, time: f64, shutter: f64) -> Composite {
+        let moves =
+            !(self.translation.is_empty() && self.rotation.is_empty() && self.scale.is_empty());
+        let color = self.color.at(time);
+        let adapt = |p: &Primitive| {
+            let mut p = p.clone();
+            if let Some(color) = color {
+                p = Recolored { obj: p, color }.build();
+            }
+            if moves && shutter > 0. {
+                let keys = (0..=Timeline::STEPS)
+                    .map(|k| self.keyframe(time + shutter * k as f64 / Timeline::STEPS as f64))
+                    .collect();
+                p = Moving {
+                    obj: p,
+                    motion: Motion::keyframes(keys),
+                }
+                .build();
+            } else if moves {
+                p = p.transform(self.keyframe(time).transform());
+            }
+            p
+        };
+        self.obj
+            .iter()
+            .map(|Interaction(inside, outside)| {
+                Interaction(
+                    inside.iter().map(adapt).collect(),
+                    outside.iter().map(adapt).collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Keyframed animation of the camera and of objects
+#[derive(Clone)]
+pub struct Timeline {
+    /// Frames per unit of time
+    pub fps: f64,
+    /// Duration during which the shutter stays open for each frame
+    pub shutter: f64,
+    /// Overrides the camera of the scene when not empty
+    pub camera: Track<CameraKey>,
+    pub objects: Vec<Animated>,
+}
+
+impl Timeline {
+    /// Number of positions of moving objects recorded while the shutter is open
+    const STEPS: usize = 8;
+
+    pub fn new(fps: f64) -> Self {
+        Self {
+            fps,
+            shutter: 0.,
+            camera: Track::new(),
+            objects: Vec::new(),
+        }
+    }
+
+    /// Add an object and return its index in `objects`
+    pub fn add(&mut self, obj: Composite) -> usize {
+        self.objects.push(Animated::new(obj));
+        self.objects.len() - 1
+    }
+
+    /// Scene of `base` as it is at the given instant
+    ///
+    /// When the camera is animated, its projection and focus distance are those of the
+    /// camera of `base`, but not its motion, which is tied to the instant of `base`.
+    pub fn frame(&self, base: &Builder, time: f64) -> Builder {
+        let mut world = base.world.clone();
+        for obj in &self.objects {
+            world.push_vec(obj.at(time, self.shutter));
+        }
+        let mut cam = match self.camera.at(time) {
+            Some(key) => {
+                let ratio = match base.stereo {
+                    Some(s) => s.layout.eye_aspect(base.wth, base.hgt),
+                    None => base.wth as f64 / base.hgt as f64,
+                };
+                key.camera(ratio, base.cam.projection(), base.cam.focus())
+            }
+            None => base.cam.clone(),
+        };
+        cam.set_shutter(time, time + self.shutter);
+        Builder {
+            cam,
+            world,
+            ..base.clone()
+        }
+    }
+
+    /// Render the frames of the range as `{name}-{frame}`, where `name` is the one of `base`
+    pub fn render(&self, base: &Builder, frames: Range<usize>) {
+        for i in frames {
+            let mut build = self.frame(base, i as f64 / self.fps);
+            build.name = format!("{}-{}", base.name, i);
+            render(build);
+        }
+    }
+}
+
+/// Object whose textures take another color
+struct Recolored {
+    obj: Primitive,
+    color: RGB,
+}
+
+impl Recolored {
+    fn build(self) -> Primitive {
+        Primitive(Arc::new(self))
+    }
+}
+
+impl Hit for Recolored {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        match self.obj.hit(r) {
+            HitRecord::Hit(h) => HitRecord::Hit(ActiveHit {
+                texture: h.texture.with_color(self.color),
+                ..h
+            }),
+            HitRecord::Blank => HitRecord::Blank,
+        }
+    }
+
+    fn texture(&self) -> Texture {
+        self.obj.texture().with_color(self.color)
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.obj.inside(pos)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.obj.bounds()
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        let mut spans = self.obj.intervals(r)?;
+        for s in &mut spans {
+            s.enter.texture = s.enter.texture.with_color(self.color);
+            s.exit.texture = s.exit.texture.with_color(self.color);
+        }
+        Some(spans)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::internal::*;
+use std::ops;
+
+/// Affine transformation along with its inverse, so that both directions are cheap
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub matrix: Mat4,
+    pub inverse: Mat4,
+}
+
+impl Transform {
+    /// Panics if the matrix is not invertible
+    pub fn new(matrix: Mat4) -> Self {
+        Self {
+            matrix,
+            inverse: matrix.inverse(),
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self {
+            matrix: Mat4::identity(),
+            inverse: Mat4::identity(),
+        }
+    }
+
+    pub fn translation(v: Vec3) -> Self {
+        Self {
+            matrix: Mat4::translation(v),
+            inverse: Mat4::translation(-v),
+        }
+    }
+
+    /// Scale by a different factor along each axis
+    pub fn scaling(v: Vec3) -> Self {
+        Self {
+            matrix: Mat4::scaling(v),
+            inverse: Mat4::scaling(Vec3(1. / v.0, 1. / v.1, 1. / v.2)),
+        }
+    }
+
+    /// Rotation of `angle` degrees around `axis`
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        Self::from_quat(Quat::axis_angle(axis, angle))
+    }
+
+    /// Rotation given by Euler angles in degrees, see `Quat::euler`
+    pub fn euler(yaw: f64, pitch: f64, roll: f64) -> Self {
+        Self::from_quat(Quat::euler(yaw, pitch, roll))
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        let q = q.normalize();
+        let m = Mat4::affine(q.to_mat3(), Vec3(0., 0., 0.));
+        Self {
+            matrix: m,
+            inverse: m.transpose(),
+        }
+    }
+
+    /// Place an object at `eye`, with its z axis pointing away from `target` and its
+    /// y axis as close as possible to `up`, as a camera that looks at `target`
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let w = (eye - target).unit();
+        let u = up.cross(w).unit();
+        let v = w.cross(u);
+        let rot = Mat3::from_columns(u, v, w);
+        Self {
+            matrix: Mat4::affine(rot, eye),
+            inverse: Mat4::affine(rot.transpose(), -rot.transpose().apply(eye)),
+        }
+    }
+
+    /// Transformation applied around a fixed point instead of the origin
+    pub fn around(self, center: Vec3) -> Self {
+        Self::translation(center) * self * Self::translation(-center)
+    }
+
+    pub fn invert(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn apply_point(&self, p: Vec3) -> Vec3 {
+        self.matrix.apply_point(p)
+    }
+
+    pub fn apply_vector(&self, v: Vec3) -> Vec3 {
+        self.matrix.apply_vector(v)
+    }
+
+    /// Normals are transformed by the inverse transpose so that they remain orthogonal
+    /// to the surface, the result is not normalized
+    pub fn apply_normal(&self, n: Vec3) -> Vec3 {
+        self.inverse.linear().transpose().apply(n)
+    }
+
+    /// Bring a ray into the coordinates before the transformation
+    ///
+    /// The direction is not normalized so that `t` is the same in both spaces.
+    pub fn unapply_ray(&self, r: &Ray) -> Ray {
+        Ray {
+            orig: self.inverse.apply_point(r.orig),
+            dir: self.inverse.apply_vector(r.dir),
+            time: r.time,
+        }
+    }
+}
+
+/// Composition: `(a * b)` applies `b` first, then `a`
+impl ops::Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            matrix: self.matrix * other.matrix,
+            inverse: other.inverse * self.inverse,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).len() < 1e-9
+    }
+
+    #[test]
+    fn inverse_is_consistent() {
+        let t = Transform::translation(Vec3(1., 2., 3.))
+            * Transform::rotation(Vec3(1., 1., 0.), 30.)
+            * Transform::scaling(Vec3(2., 0.5, 1.));
+        let p = Vec3(0.3, -0.7, 1.1);
+        assert!(close(t.invert().apply_point(t.apply_point(p)), p));
+        let m = t.matrix.inverse();
+        assert!(close(m.apply_point(p), t.inverse.apply_point(p)));
+    }
+
+    #[test]
+    fn normals_stay_orthogonal() {
+        let t = Transform::scaling(Vec3(3., 1., 1.)) * Transform::rotation(Vec3(0., 0., 1.), 45.);
+        let tangent = Vec3(1., -1., 0.);
+        let normal = Vec3(1., 1., 0.);
+        let n = t.apply_normal(normal);
+        assert!(n.dot(t.apply_vector(tangent)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn look_at_frame() {
+        let eye = Vec3(0., 0., 5.);
+        let t = Transform::look_at(eye, Vec3(0., 0., 0.), Vec3(0., 1., 0.));
+        assert!(close(t.apply_point(Vec3(0., 0., 0.)), eye));
+        assert!(close(t.apply_vector(Vec3(0., 0., -1.)), Vec3(0., 0., -1.)));
+        assert!(close(t.apply_vector(Vec3(0., 1., 0.)), Vec3(0., 1., 0.)));
+        assert!(close(t.invert().apply_point(eye), Vec3(0., 0., 0.)));
+    }
+
+    #[test]
+    fn around_center() {
+        let c = Vec3(1., 1., 0.);
+        let t = Transform::rotation(Vec3(0., 0., 1.), 180.).around(c);
+        assert!(close(t.apply_point(c), c));
+        assert!(close(t.apply_point(Vec3(2., 1., 0.)), Vec3(0., 1., 0.)));
+    }
+
+    #[test]
+    fn ray_parameter_preserved() {
+        let t = Transform::scaling(Vec3(2., 2., 2.));
+        let r = Ray::new(Vec3(4., 0., 0.), Vec3(-2., 0., 0.));
+        let local = t.unapply_ray(&r);
+        assert!(close(
+            local.project(1.5),
+            t.invert().apply_point(r.project(1.5))
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::internal::*;
+use std::sync::Arc;
+
+/// Any object seen through an affine transformation
+///
+/// Rays are brought back into the coordinates of the object, and normals are
+/// transformed by the inverse transpose of the matrix so that they remain orthogonal
+/// to the surface even under non-uniform scaling.
+pub struct Transformed {
+    pub obj: Primitive,
+    /// From the coordinates of the object to the coordinates of the scene
+    pub transform: Transform,
+}
+
+impl Transformed {
+    pub fn build(self) -> Primitive {
+        Primitive(Arc::new(self))
+    }
+}
+
+impl Hit for Transformed {
+    fn hit(&self, r: &Ray) -> HitRecord {
+        match self.obj.hit(&self.transform.unapply_ray(r)) {
+            HitRecord::Blank => HitRecord::Blank,
+            HitRecord::Hit(h) => HitRecord::make(
+                h.t,
+                r.project(h.t),
+                self.transform.apply_normal(h.normal),
+                h.texture,
+            ),
+        }
+    }
+
+    fn texture(&self) -> Texture {
+        self.obj.texture()
+    }
+
+    fn inside(&self, pos: Vec3) -> bool {
+        self.obj.inside(self.transform.inverse.apply_point(pos))
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.obj
+            .bounds()
+            .map(|b| b.transform(&self.transform.matrix))
+    }
+
+    fn intervals(&self, r: &Ray) -> Option<Vec<Span>> {
+        // `t` is the same in both coordinates, only the normals need to be transformed
+        let mut spans = self.obj.intervals(&self.transform.unapply_ray(r))?;
+        for s in &mut spans {
+            s.enter.normal = self.transform.apply_normal(s.enter.normal);
+            s.exit.normal = self.transform.apply_normal(s.exit.normal);
+        }
+        Some(spans)
+    }
+}
+
+/// Objects that can be moved, turned and stretched
+pub trait Transformable: Sized {
+    /// Apply an arbitrary affine transformation
+    fn transform(self, t: Transform) -> Self;
+
+    fn translate(self, v: Vec3) -> Self {
+        self.transform(Transform::translation(v))
+    }
+
+    /// Rotation of `angle` degrees around an axis that goes through `center`
+    fn rotate(self, center: Vec3, axis: Vec3, angle: f64) -> Self {
+        self.transform(Transform::rotation(axis, angle).around(center))
+    }
+
+    /// Scale along each axis relative to `center`
+    fn scale(self, center: Vec3, factors: Vec3) -> Self {
+        self.transform(Transform::scaling(factors).around(center))
+    }
+}
+
+impl Transformable for Primitive {
+    fn transform(self, t: Transform) -> Self {
+        Transformed {
+            obj: self,
+            transform: t,
+        }
+        .build()
+    }
+}
+
+impl Transformable for Interaction {
+    fn transform(self, t: Transform) -> Self {
+        let Interaction(inside, outside) = self;
+        Interaction(
+            inside.into_iter().map(|p| p.transform(t)).collect(),
+            outside.into_iter().map(|p| p.transform(t)).collect(),
+        )
+    }
+}
+
+impl Transformable for Composite {
+    fn transform(self, t: Transform) -> Self {
+        self.into_iter().map(|i| i.transform(t)).collect()
+    }
+}
@@ -0,0 +1,151 @@
+use crate::internal::*;
+
+/// Maximum number of vertices around a tube
+const MAX_SIDES: usize = 256;
+
+/// How the points of a `Tube` define its center line
+#[derive(Clone, Copy, Debug)]
+pub enum Curve {
+    /// Smooth curve that goes through all of the points
+    CatmullRom,
+    /// Chain of cubic Bézier curves: the points are the first point, then two control
+    /// points and an end point for each piece
+    Bezier,
+}
+
+/// Tube of variable radius swept along a curve and closed at both ends, rendered as a mesh
+#[derive(Clone)]
+pub struct Tube {
+    pub points: Vec<Vec3>,
+    /// Radius at each point, interpolated in the same way as the points
+    pub radii: Vec<f64>,
+    pub curve: Curve,
+    /// Maximum distance between the surface and the triangles that approximate it
+    pub tolerance: f64,
+    pub texture: Texture,
+}
+
+impl Tube {
+    /// Bézier control points of each piece of the curve, with the radii in the
+    /// first coordinate of another curve
+    fn pieces(&self) -> Vec<([Vec3; 4], [Vec3; 4])> {
+        let pts = &self.points;
+        let rad = self
+            .radii
+            .iter()
+            .map(|&r| Vec3(r, 0., 0.))
+            .collect::<Vec<_>>();
+        match self.curve {
+            Curve::CatmullRom => {
+                let n = pts.len() as isize;
+                let at = |i: isize| i.max(0).min(n - 1) as usize;
+                (0..n - 1)
+                    .map(|i| {
+                        let (a, b, c, d) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+                        (
+                            spline::catmull_rom_to_bezier(pts[a], pts[b], pts[c], pts[d]),
+                            spline::catmull_rom_to_bezier(rad[a], rad[b], rad[c], rad[d]),
+                        )
+                    })
+                    .collect()
+            }
+            Curve::Bezier => {
+                assert_eq!(pts.len() % 3, 1, "A Bézier chain needs 3k+1 points");
+                (0..pts.len() / 3)
+                    .map(|k| {
+                        let i = 3 * k;
+                        (
+                            [pts[i], pts[i + 1], pts[i + 2], pts[i + 3]],
+                            [rad[i], rad[i + 1], rad[i + 2], rad[i + 3]],
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Approximate the tube by triangles
+    pub fn tessellate(&self) -> Mesh {
+        // Center, direction and radius of each ring of vertices
+        let mut rings: Vec<(Vec3, Vec3, f64)> = Vec::new();
+        for (ctrl, rad) in self.pieces() {
+            let n = spline::flattening(&ctrl, self.tolerance);
+            let start = if rings.is_empty() { 0 } else { 1 };
+            for s in start..=n {
+                let t = s as f64 / n as f64;
+                let dir = spline::bezier_tangent(&ctrl, t);
+                let dir = if dir.len() > EPSILON {
+                    dir
+                } else {
+                    ctrl[3] - ctrl[0]
+                };
+                rings.push((
+                    spline::bezier(&ctrl, t),
+                    dir.unit(),
+                    spline::bezier(&rad, t).0,
+                ));
+            }
+        }
+        let rmax = rings.iter().fold(0., |m: f64, r| m.max(r.2));
+        let angle = (1. - self.tolerance / rmax).clamp(-1., 1.).acos();
+        let sides = ((std::f64::consts::PI / angle).ceil() as usize).clamp(6, MAX_SIDES);
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut faces = Vec::new();
+        // Rotation minimizing frame, obtained by projecting the previous one
+        let t0 = rings[0].1;
+        let a = if t0.0.abs() < 0.9 {
+            Vec3(1., 0., 0.)
+        } else {
+            Vec3(0., 1., 0.)
+        };
+        let mut side = (a - t0 * a.dot(t0)).unit();
+        let last = rings.len() - 1;
+        for (i, &(center, dir, radius)) in rings.iter().enumerate() {
+            side = (side - dir * side.dot(dir)).unit();
+            let up = dir.cross(side);
+            // Slope of the radius, which tilts the normals
+            let (p, q) = (rings[i.saturating_sub(1)], rings[(i + 1).min(last)]);
+            let slope = (q.2 - p.2) / (q.0 - p.0).len().max(EPSILON);
+            for j in 0..sides {
+                let theta = 2. * std::f64::consts::PI * j as f64 / sides as f64;
+                let radial = side * theta.cos() + up * theta.sin();
+                vertices.push(center + radial * radius);
+                normals.push((radial - dir * slope).unit());
+            }
+        }
+        for i in 0..last {
+            for j in 0..sides {
+                let a = i * sides + j;
+                let b = i * sides + (j + 1) % sides;
+                faces.push([a, b, b + sides]);
+                faces.push([a, b + sides, a + sides]);
+            }
+        }
+        // Caps, with their own vertices so that they are shaded flat
+        for &(i, sign) in &[(0, -1.), (last, 1.)] {
+            let (center, dir, _) = rings[i];
+            let c = vertices.len();
+            vertices.push(center);
+            normals.push(dir * sign);
+            for j in 0..sides {
+                vertices.push(vertices[i * sides + j]);
+                normals.push(dir * sign);
+            }
+            for j in 0..sides {
+                let (a, b) = (c + 1 + j, c + 1 + (j + 1) % sides);
+                faces.push(if sign > 0. { [c, a, b] } else { [c, b, a] });
+            }
+        }
+        Mesh {
+            vertices,
+            normals,
+            faces,
+            texture: self.texture,
+        }
+    }
+
+    pub fn build(self) -> Primitive {
+        self.tessellate().build()
+    }
+}
@@ -4,6 +4,9 @@ use crate::internal::*;
 #[derive(Clone, Default)]
 pub struct World {
     obj: Vec<Interaction>,
+    /// Bounding box of each object, to quickly discard those that are not on the
+    /// path of a ray
+    bounds: Vec<Option<Aabb>>,
     pub background: Option<RGB>,
 }
 
@@ -12,25 +15,33 @@ impl World {
     pub fn new() -> Self {
         Self {
             obj: Vec::new(),
+            bounds: Vec::new(),
             background: None,
         }
     }
 
     /// Add an object to the scene
     pub fn push(&mut self, x: Interaction) {
+        self.bounds.push(x.bounds().map(|b| b.pad(EPSILON)));
         self.obj.push(x);
     }
 
     /// Unwrap a vector of objects and add them one by one
     pub fn push_vec(&mut self, v: Composite) {
         for x in v {
-            self.obj.push(x)
+            self.push(x)
         }
     }
 
     /// Remove all objects
     pub fn clear(&mut self) {
         self.obj.clear();
+        self.bounds.clear();
+    }
+
+    /// Box around the bounded objects of the scene, see `bounds_of_bounded`
+    pub fn bounds(&self) -> Option<Aabb> {
+        bounds_of_bounded(&self.obj)
     }
 
     /// Override background given by the Sky
@@ -40,50 +51,7 @@ impl World {
 
     /// Distribute hit on all objects (including inside/outside tests)
     pub fn hit(&self, r: &Ray) -> HitRecord {
-        let mut rec = HitRecord::Blank;
-        for group in &self.obj {
-            let mut record = HitRecord::Blank;
-            for i in 0..group.0.len() {
-                let mut ray = *r;
-                let mut offset = 0.0;
-                let item = &group.0[i];
-                loop {
-                    match item.hit(&ray) {
-                        HitRecord::Blank => break,
-                        HitRecord::Hit(h) => {
-                            if Interaction::all_inside_except(h.pos, &group.0, i)
-                                && Interaction::all_outside_except(h.pos, &group.1, group.1.len())
-                            {
-                                record.compare(HitRecord::Hit(h.later(offset)));
-                            }
-                            ray.orig = h.pos + ray.dir * EPSILON;
-                            offset += h.t;
-                        }
-                    }
-                }
-            }
-            for i in 0..group.1.len() {
-                let mut ray = *r;
-                let mut offset = 0.0;
-                let item = &group.1[i];
-                loop {
-                    match item.hit(&ray) {
-                        HitRecord::Blank => break,
-                        HitRecord::Hit(h) => {
-                            if Interaction::all_inside_except(h.pos, &group.0, group.0.len())
-                                && Interaction::all_outside_except(h.pos, &group.1, i)
-                            {
-                                record.compare(HitRecord::Hit(h.later(offset)));
-                            }
-                            ray.orig = h.pos + ray.dir * EPSILON;
-                            offset += h.t;
-                        }
-                    }
-                }
-            }
-            rec.compare(record);
-        }
-        rec
+        nearest_hit(&self.obj, &self.bounds, r)
     }
 
     /// Get optical index and color of a point in space
@@ -91,9 +59,7 @@ impl World {
     /// Only useful if the scene includes `Dielectric` materials
     pub fn caracteristics(&self, pos: Vec3) -> (f64, RGB) {
         for group in &self.obj {
-            if Interaction::all_inside_except(pos, &group.0, group.0.len())
-                && Interaction::all_outside_except(pos, &group.1, group.1.len())
-            {
+            if group.contains(pos) {
                 for item in &group.0 {
                     if let Texture::Dielectric(shade, idx) = item.texture() {
                         return (idx, shade);
@@ -105,6 +71,52 @@ impl World {
     }
 }
 
+/// Box around the bounded parts of a group of objects, such as a `Composite`
+///
+/// Unbounded objects (planes) are left out, `None` if none of the objects is bounded.
+/// Suited to framing a scene.
+pub fn bounds_of_bounded(obj: &[Interaction]) -> Option<Aabb> {
+    obj.iter()
+        .filter_map(|x| x.bounds())
+        .filter(|b| !b.is_empty())
+        .fold(None, |acc, b| Some(acc.map_or(b, |a: Aabb| a.union(b))))
+}
+
+/// Box around the whole of a group of objects, such as a `Composite`
+///
+/// `None` as soon as one of the objects is unbounded, or if there are no objects.
+/// Suited to telling which part of a scene an object can affect.
+pub fn bounds_of_all(obj: &[Interaction]) -> Option<Aabb> {
+    let mut parts = obj.iter().map(|x| x.bounds());
+    let first = parts.next()??;
+    parts.try_fold(first, |acc, b| Some(acc.union(b?)))
+}
+
+/// Nearest intersection with a collection of objects
+///
+/// Objects whose bounding box is not crossed by the ray, or is entirely behind
+/// the nearest intersection found so far, are skipped.
+pub fn nearest_hit(obj: &[Interaction], bounds: &[Option<Aabb>], r: &Ray) -> HitRecord {
+    let mut rec = HitRecord::Blank;
+    for (group, bounds) in obj.iter().zip(bounds) {
+        if let Some(b) = bounds {
+            match b.range(r) {
+                None => continue,
+                Some((_, tmax)) if tmax < 0. => continue,
+                Some((tmin, _)) => {
+                    if let HitRecord::Hit(h) = &rec {
+                        if h.t < tmin {
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+        rec.compare(group.hit(r));
+    }
+    rec
+}
+
 /// [Schlick's Appriximation](https://en.wikipedia.org/wiki/Schlick's_approximation)
 fn schlick(cos: f64, n1: f64, n2: f64) -> f64 {
     let r = ((n1 - n2) / (n1 + n2)).powi(2);
@@ -116,7 +128,7 @@ pub fn scatter(incident: &Ray, record: ActiveHit, w: &World) -> Option<(RGB, Ray
     match record.texture {
         Texture::Lambertian(albedo) => {
             let reflec = incident.dir.unit().reflect(record.normal);
-            let scattered = Ray::new(record.pos, reflec + Vec3::random_unit() * 0.8);
+            let scattered = incident.spawn(record.pos, reflec + Vec3::random_unit() * 0.8);
             let attenuation = albedo;
             let normal = {
                 if scattered.dir.dot(record.normal) > 0.0 {
@@ -133,7 +145,8 @@ pub fn scatter(incident: &Ray, record: ActiveHit, w: &World) -> Option<(RGB, Ray
         }
         Texture::Metal(albedo, fuzziness) => {
             let reflec = incident.dir.unit().reflect(record.normal);
-            let scattered = Ray::new(record.pos, reflec + Vec3::random_unit() * fuzziness * 0.8);
+            let scattered =
+                incident.spawn(record.pos, reflec + Vec3::random_unit() * fuzziness * 0.8);
             let attenuation = albedo;
             let normal = {
                 if scattered.dir.dot(record.normal) > 0.0 {
@@ -158,14 +171,8 @@ pub fn scatter(incident: &Ray, record: ActiveHit, w: &World) -> Option<(RGB, Ray
                     record.normal
                 }
             };
-            let tmp_ray_succ = Ray {
-                orig: record.pos,
-                dir: ext_normal,
-            };
-            let tmp_ray_prev = Ray {
-                orig: record.pos,
-                dir: -ext_normal,
-            };
+            let tmp_ray_succ = incident.spawn(record.pos, ext_normal);
+            let tmp_ray_prev = incident.spawn(record.pos, -ext_normal);
             let mid_caract = |r| match w.hit(&r) {
                 HitRecord::Blank => (1., RGB(1., 1., 1.), 1.),
                 HitRecord::Hit(h) => {
@@ -181,33 +188,15 @@ pub fn scatter(incident: &Ray, record: ActiveHit, w: &World) -> Option<(RGB, Ray
             let cos = -incident.dir.unit().dot(ext_normal);
 
             match incident.dir.refract(ext_normal, rel_idx) {
-                None => Some((
-                    shade,
-                    Ray {
-                        orig: record.pos,
-                        dir: reflected,
-                    },
-                )),
+                None => Some((shade, incident.spawn(record.pos, reflected))),
                 Some(refracted) => {
                     let prob_reflect = schlick(cos, i_idx, r_idx);
                     if rand::random::<f64>() < prob_reflect {
-                        Some((
-                            shade,
-                            Ray {
-                                orig: record.pos,
-                                dir: reflected,
-                            },
-                        ))
+                        Some((shade, incident.spawn(record.pos, reflected)))
                     } else {
                         let shade = RGB(1., 1., 1.) - (RGB(1., 1., 1.) - i_shade) * i_len * 1.5;
 
-                        Some((
-                            shade.validate(),
-                            Ray {
-                                orig: record.pos,
-                                dir: refracted,
-                            },
-                        ))
+                        Some((shade.validate(), incident.spawn(record.pos, refracted)))
                     }
                 }
             }
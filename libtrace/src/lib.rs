@@ -2,5 +2,7 @@
 pub mod composite;
 /// All of the objects and functions required for the proper execution of the physics engine
 pub mod internal;
+/// Assembly of frames into animations
+pub mod movie;
 /// Main function
 pub mod render;
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::process::Command;
+
+/// Container in which the frames of a movie are assembled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Animated GIF, limited to 256 colors shared by all frames
+    Gif,
+    /// Animated PNG, lossless
+    Apng,
+    /// Directory with a copy of each frame
+    Sequence,
+    /// Video encoded by an external `ffmpeg` program
+    Ffmpeg,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gif" => Some(Format::Gif),
+            "apng" => Some(Format::Apng),
+            "sequence" => Some(Format::Sequence),
+            "ffmpeg" => Some(Format::Ffmpeg),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Gif => "gif",
+            Format::Apng => "apng",
+            Format::Sequence => "sequence",
+            Format::Ffmpeg => "ffmpeg",
+        }
+    }
+
+    /// File or directory created from the name of the movie
+    pub fn output(&self, name: &str) -> String {
+        match self {
+            Format::Gif => format!("{}.gif", name),
+            Format::Apng => format!("{}.png", name),
+            Format::Sequence => name.to_string(),
+            Format::Ffmpeg => format!("{}.avi", name),
+        }
+    }
+}
+
+/// Pixels of an image, row by row from the top
+pub struct Image {
+    pub wth: usize,
+    pub hgt: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Image {
+    /// Read a plain text (P3) PPM file, as written by `render`
+    pub fn read_ppm(path: &str) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut tokens = text.split_whitespace();
+        if tokens.next() != Some("P3") {
+            return Err(invalid(format!("{} is not a P3 image", path)));
+        }
+        let mut number = || -> io::Result<usize> {
+            tokens
+                .next()
+                .and_then(|t| t.parse().ok())
+                .ok_or_else(|| invalid(format!("{} is truncated", path)))
+        };
+        let wth = number()?;
+        let hgt = number()?;
+        let max = number()?.max(1);
+        let mut pixels = Vec::with_capacity(wth * hgt);
+        for _ in 0..wth * hgt {
+            let mut px = [0; 3];
+            for c in &mut px {
+                *c = (number()? * 255 / max) as u8;
+            }
+            pixels.push(px);
+        }
+        Ok(Self { wth, hgt, pixels })
+    }
+}
+
+/// Assemble the frames into a movie named after `name` (see `Format::output`)
+///
+/// All frames must have the same size.
+pub fn assemble(frames: &[String], name: &str, fps: f64, format: Format) -> io::Result<()> {
+    if frames.is_empty() {
+        return Err(invalid(String::from("No frames to assemble")));
+    }
+    let out = format.output(name);
+    match format {
+        Format::Sequence => {
+            fs::create_dir_all(&out)?;
+            for (i, f) in frames.iter().enumerate() {
+                fs::copy(f, format!("{}/frame-{:05}.ppm", out, i))?;
+            }
+            Ok(())
+        }
+        Format::Ffmpeg => {
+            let list = format!("{}.frames.txt", name);
+            let mut txt = String::new();
+            for f in frames {
+                txt.push_str(&format!("file '{}'\nduration {}\n", f, 1. / fps));
+            }
+            fs::write(&list, txt)?;
+            let status = Command::new("ffmpeg")
+                .args(["-y", "-f", "concat", "-safe", "0", "-i", &list])
+                .args(["-vcodec", "libx264", &out])
+                .status();
+            fs::remove_file(&list)?;
+            if status?.success() {
+                Ok(())
+            } else {
+                Err(io::Error::other("ffmpeg failed"))
+            }
+        }
+        Format::Gif | Format::Apng => {
+            let images = frames
+                .iter()
+                .map(|f| Image::read_ppm(f))
+                .collect::<io::Result<Vec<_>>>()?;
+            if images
+                .iter()
+                .any(|i| i.wth != images[0].wth || i.hgt != images[0].hgt)
+            {
+                return Err(invalid(String::from("Frames have different sizes")));
+            }
+            let mut f = BufWriter::new(File::create(&out)?);
+            if format == Format::Gif {
+                write_gif(&mut f, &images, fps)?;
+            } else {
+                write_apng(&mut f, &images, fps)?;
+            }
+            f.flush()
+        }
+    }
+}
+
+/// Palette of at most `size` colors by median cut: the box of colors with the widest
+/// range on one channel is split in two halves until there are enough boxes
+fn median_cut(mut colors: Vec<[u8; 3]>, size: usize) -> Vec<[u8; 3]> {
+    let widest = |b: &[[u8; 3]]| {
+        (0..3)
+            .map(|c| {
+                let lo = b.iter().map(|p| p[c]).min().unwrap();
+                let hi = b.iter().map(|p| p[c]).max().unwrap();
+                (hi - lo, c)
+            })
+            .max()
+            .unwrap()
+    };
+    colors.sort_unstable();
+    colors.dedup();
+    let mut boxes = vec![colors];
+    while boxes.len() < size {
+        let (idx, (range, chan)) = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (i, widest(b)))
+            .max_by_key(|x| x.1)
+            .unwrap();
+        if range == 0 {
+            break;
+        }
+        let mut b = boxes.swap_remove(idx);
+        b.sort_unstable_by_key(|p| p[chan]);
+        let upper = b.split_off(b.len() / 2);
+        boxes.push(b);
+        boxes.push(upper);
+    }
+    boxes
+        .iter()
+        .map(|b| {
+            let mut sum = [0usize; 3];
+            for p in b {
+                for c in 0..3 {
+                    sum[c] += p[c] as usize;
+                }
+            }
+            [
+                (sum[0] / b.len()) as u8,
+                (sum[1] / b.len()) as u8,
+                (sum[2] / b.len()) as u8,
+            ]
+        })
+        .collect()
+}
+
+/// Index of the color of the palette that is closest to each pixel, with a cache on
+/// the 5 most significant bits of each channel
+fn quantize(img: &Image, palette: &[[u8; 3]], cache: &mut HashMap<u16, u8>) -> Vec<u8> {
+    img.pixels
+        .iter()
+        .map(|p| {
+            let key = ((p[0] as u16 >> 3) << 10) | ((p[1] as u16 >> 3) << 5) | (p[2] as u16 >> 3);
+            *cache.entry(key).or_insert_with(|| {
+                let dist = |q: &[u8; 3]| {
+                    (0..3)
+                        .map(|c| (p[c] as i32 - q[c] as i32).pow(2))
+                        .sum::<i32>()
+                };
+                (0..palette.len())
+                    .min_by_key(|&i| dist(&palette[i]))
+                    .unwrap() as u8
+            })
+        })
+        .collect()
+}
+
+fn gif_error(e: gif::EncodingError) -> io::Error {
+    match e {
+        gif::EncodingError::Io(e) => e,
+        e => invalid(e.to_string()),
+    }
+}
+
+fn write_gif<W: Write>(f: &mut W, images: &[Image], fps: f64) -> io::Result<()> {
+    let (wth, hgt) = (images[0].wth as u16, images[0].hgt as u16);
+    // A sample of all frames is enough to choose the palette
+    let total: usize = images.iter().map(|i| i.pixels.len()).sum();
+    let step = (total / 65536).max(1);
+    let sample = images
+        .iter()
+        .flat_map(|i| i.pixels.iter())
+        .step_by(step)
+        .copied()
+        .collect();
+    let palette = median_cut(sample, 256);
+    let mut enc = gif::Encoder::new(f, wth, hgt, &palette.concat()).map_err(gif_error)?;
+    enc.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+    let delay = (100. / fps).round() as u16;
+    let mut cache = HashMap::new();
+    for img in images {
+        let mut frame =
+            gif::Frame::from_indexed_pixels(wth, hgt, &quantize(img, &palette, &mut cache), None);
+        frame.delay = delay;
+        enc.write_frame(&frame).map_err(gif_error)?;
+    }
+    Ok(())
+}
+
+fn write_apng<W: Write>(f: &mut W, images: &[Image], fps: f64) -> io::Result<()> {
+    let mut enc = png::Encoder::new(f, images[0].wth as u32, images[0].hgt as u32);
+    enc.set_color(png::ColorType::Rgb);
+    enc.set_depth(png::BitDepth::Eight);
+    enc.set_animated(images.len() as u32, 0)?;
+    enc.set_frame_delay((1000. / fps).round() as u16, 1000)?;
+    let mut w = enc.write_header()?;
+    for img in images {
+        w.write_image_data(&img.pixels.concat())?;
+    }
+    w.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    /// Fresh directory for the files of a test
+    fn scratch(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("movie-{}-{}", test, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Frames with few enough colors that the GIF palette is exact
+    fn frames(dir: &Path) -> (Vec<String>, Vec<Vec<[u8; 3]>>) {
+        let (wth, hgt) = (7, 5);
+        let mut names = Vec::new();
+        let mut pixels = Vec::new();
+        for k in 0..3 {
+            let px = (0..wth * hgt)
+                .map(|n| [(n % wth * 30) as u8, (n / wth * 40) as u8, k * 100])
+                .collect::<Vec<_>>();
+            let mut txt = format!("P3\n{} {}\n255\n", wth, hgt);
+            for p in &px {
+                txt.push_str(&format!("{} {} {}\n", p[0], p[1], p[2]));
+            }
+            let name = dir.join(format!("{}.ppm", k)).to_str().unwrap().to_string();
+            fs::write(&name, txt).unwrap();
+            names.push(name);
+            pixels.push(px);
+        }
+        (names, pixels)
+    }
+
+    #[test]
+    fn read_ppm() {
+        let dir = scratch("ppm");
+        let name = dir.join("img.ppm");
+        fs::write(&name, "P3\n2 1\n1\n0 1 0\n1 1 1\n").unwrap();
+        let img = Image::read_ppm(name.to_str().unwrap()).unwrap();
+        assert_eq!((img.wth, img.hgt), (2, 1));
+        assert_eq!(img.pixels, vec![[0, 255, 0], [255, 255, 255]]);
+        fs::write(&name, "P3\n2 1\n255\n0 1 0\n1 1\n").unwrap();
+        assert!(Image::read_ppm(name.to_str().unwrap()).is_err());
+        fs::write(&name, "P6\n2 1\n255\n").unwrap();
+        assert!(Image::read_ppm(name.to_str().unwrap()).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn gif_round_trip() {
+        let dir = scratch("gif");
+        let (names, pixels) = frames(&dir);
+        let movie = dir.join("movie").to_str().unwrap().to_string();
+        assemble(&names, &movie, 10., Format::Gif).unwrap();
+        let mut opts = gif::DecodeOptions::new();
+        opts.set_color_output(gif::ColorOutput::RGBA);
+        let mut dec = opts
+            .read_info(File::open(Format::Gif.output(&movie)).unwrap())
+            .unwrap();
+        for px in &pixels {
+            let frame = dec.read_next_frame().unwrap().unwrap();
+            assert_eq!(frame.delay, 10);
+            let rgb = frame
+                .buffer
+                .chunks(4)
+                .map(|c| [c[0], c[1], c[2]])
+                .collect::<Vec<_>>();
+            assert_eq!(&rgb, px);
+        }
+        assert!(dec.read_next_frame().unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn apng_round_trip() {
+        let dir = scratch("apng");
+        let (names, pixels) = frames(&dir);
+        let movie = dir.join("movie").to_str().unwrap().to_string();
+        assemble(&names, &movie, 10., Format::Apng).unwrap();
+        let dec = png::Decoder::new(File::open(Format::Apng.output(&movie)).unwrap());
+        let mut reader = dec.read_info().unwrap();
+        assert_eq!(reader.info().animation_control.unwrap().num_frames, 3);
+        let mut buf = vec![0; reader.output_buffer_size()];
+        for px in &pixels {
+            reader.next_frame(&mut buf).unwrap();
+            assert_eq!(buf, px.concat());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
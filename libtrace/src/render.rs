@@ -5,8 +5,10 @@ use std::sync::{Arc, Barrier};
 use threadpool::ThreadPool;
 
 use crate::internal::*;
+use crate::movie::Image;
 
 /// Configuration information for a single image
+#[derive(Clone)]
 pub struct Builder {
     pub name: String,
     pub silent: bool,
@@ -14,9 +16,113 @@ pub struct Builder {
     pub wth: usize,
     pub iter: usize,
     pub cam: Camera,
+    /// Render both eyes of a rig centered on `cam` instead of `cam` alone
+    pub stereo: Option<Stereo>,
+    /// Factor applied to the colors before the gamma correction, see `PhysicalCamera::exposure`
+    pub exposure: f64,
     pub world: World,
     pub sky: Sky,
     pub nbsync: usize,
+    /// Only render again the parts of a previous image that can have changed
+    pub patch: Option<Patch>,
+}
+
+/// Previous image of the same scene in which some objects were added or removed
+///
+/// Pixels whose rays all miss the changed objects and first hit a diffuse surface are
+/// copied from the previous image. Shadows and indirect lighting cast by those objects
+/// on the rest of the scene are not updated.
+#[derive(Clone)]
+pub struct Patch {
+    /// PPM file of the same size as the new image
+    pub previous: String,
+    /// Bounding boxes of the objects added to or removed from the scene
+    pub changed: Vec<Aabb>,
+}
+
+/// Arrangement of the views of both eyes in a single image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    /// Left eye on the left half
+    SideBySide,
+    /// Left eye on the top half
+    TopBottom,
+}
+
+impl Layout {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "side-by-side" => Some(Layout::SideBySide),
+            "top-bottom" => Some(Layout::TopBottom),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::SideBySide => "side-by-side",
+            Layout::TopBottom => "top-bottom",
+        }
+    }
+
+    /// Width/height aspect ratio of the view of each eye
+    pub fn eye_aspect(&self, wth: usize, hgt: usize) -> f64 {
+        match self {
+            Layout::SideBySide => wth as f64 / 2. / hgt as f64,
+            Layout::TopBottom => wth as f64 / (hgt as f64 / 2.),
+        }
+    }
+}
+
+/// Stereoscopic rendering, see `Camera::stereo`
+#[derive(Clone, Copy)]
+pub struct Stereo {
+    /// Interpupillary distance
+    pub ipd: f64,
+    /// Distance at which the views of both eyes coincide
+    pub convergence: f64,
+    pub mode: Convergence,
+    pub layout: Layout,
+}
+
+/// Cameras that share the image
+#[derive(Clone)]
+enum Views {
+    Mono(Camera),
+    Stereo(Camera, Camera, Layout),
+}
+
+impl Views {
+    fn new(build: &Builder) -> Self {
+        match build.stereo {
+            None => Views::Mono(build.cam.clone()),
+            Some(s) => {
+                let (left, right) = build.cam.stereo(s.ipd, s.convergence, s.mode);
+                Views::Stereo(left, right, s.layout)
+            }
+        }
+    }
+
+    /// Ray through a point of the whole image, given as fractions of its width and height
+    fn get_ray(&self, hfrac: f64, vfrac: f64) -> Ray {
+        match self {
+            Views::Mono(cam) => cam.get_ray(hfrac, vfrac),
+            Views::Stereo(left, right, Layout::SideBySide) => {
+                if hfrac < 0.5 {
+                    left.get_ray(hfrac * 2., vfrac)
+                } else {
+                    right.get_ray(hfrac * 2. - 1., vfrac)
+                }
+            }
+            Views::Stereo(left, right, Layout::TopBottom) => {
+                if vfrac >= 0.5 {
+                    left.get_ray(hfrac, vfrac * 2. - 1.)
+                } else {
+                    right.get_ray(hfrac, vfrac * 2.)
+                }
+            }
+        }
+    }
 }
 
 /// Create image according to build configuration.
@@ -28,6 +134,15 @@ pub fn render(build: Builder) {
         eprint!("Rendering image...\n");
         eprint!("|\x1b[50C|\x1b[1A\n");
     }
+    let views = Views::new(&build);
+    let patch = build.patch.as_ref().and_then(|p| {
+        let img = Image::read_ppm(&p.previous).ok()?;
+        if img.wth == build.wth && img.hgt == build.hgt {
+            Some(Arc::new((img, p.changed.clone())))
+        } else {
+            None
+        }
+    });
     let pool = ThreadPool::new(build.nbsync);
     let barrier = Arc::new(Barrier::new(build.nbsync + 1));
     for id in 0..build.nbsync {
@@ -36,6 +151,8 @@ pub fn render(build: Builder) {
         let rng = (id * build.hgt / build.nbsync)..((id + 1) * build.hgt / build.nbsync);
         let barrier = barrier.clone();
         let build = build.clone();
+        let views = views.clone();
+        let patch = patch.clone();
         pool.execute(move || {
             let color = &format!("\x1b[3{}m", id + 1);
             let ni = build.hgt as f64;
@@ -48,16 +165,39 @@ pub fn render(build: Builder) {
                     }
                 }
                 for j in 0..build.wth {
+                    let sample = || {
+                        let vfrac = (i as f64 + rand::random::<f64>()) / ni;
+                        let hfrac = (j as f64 + rand::random::<f64>()) / nj;
+                        views.get_ray(hfrac, vfrac)
+                    };
+                    // Rays that bounce off a mirror or through glass may reach the
+                    // changed objects after the first hit
+                    let diffuse = |r: &Ray| match build.world.hit(r) {
+                        HitRecord::Hit(h) => {
+                            !matches!(h.texture, Texture::Metal(..) | Texture::Dielectric(..))
+                        }
+                        HitRecord::Blank => true,
+                    };
+                    let kept = patch.as_ref().filter(|p| {
+                        (0..build.iter).all(|_| {
+                            let r = sample();
+                            p.1.iter()
+                                .all(|b| !matches!(b.range(&r), Some((_, t)) if t >= 0.))
+                                && diffuse(&r)
+                        })
+                    });
+                    if let Some(p) = kept {
+                        // Rows of the image are written from the top
+                        let px = p.0.pixels[(build.hgt - 1 - i) * build.wth + j];
+                        write!(stdout, "{} {} {} ", px[0], px[1], px[2]).unwrap();
+                        continue;
+                    }
                     let mut c = rgb::BLACK;
-                    let i = i as f64;
-                    let j = j as f64;
                     for _ in 0..build.iter {
-                        let vfrac = (i + rand::random::<f64>()) / ni;
-                        let hfrac = (j + rand::random::<f64>()) / nj;
-                        let r = build.cam.get_ray(hfrac, vfrac);
+                        let r = sample();
                         c += world::calc_color(&r, &build.world, &build.sky);
                     }
-                    write!(stdout, "{}", c / build.iter as f64).unwrap();
+                    write!(stdout, "{}", c * (build.exposure / build.iter as f64)).unwrap();
                 }
                 writeln!(stdout).unwrap();
             }
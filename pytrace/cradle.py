@@ -17,8 +17,10 @@ tr.start_movie("cradle-low-q")
 
 crad = Cradle(Vec(-5, 0, -5), 0, 10)
 crad.raise_ball(60)
+crad.shutter = cam.shutter = 0.01
 for i in range(360):
     crad.set_time(15 * i / 360)
+    cam.time = 15 * i / 360
     tr.clear()
     tr.populate(crad.build())
     cam.angle = -i
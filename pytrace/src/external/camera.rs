@@ -1,8 +1,11 @@
+use pyo3::exceptions;
 use pyo3::prelude::*;
 use pyo3::PyObjectProtocol;
+use std::vec;
 
-use crate::external::Vec;
+use crate::external::{Cfg, Construct, Prebuilt, Vec};
 use crate::internal;
+use pytrace_core::render::{Layout, Stereo};
 
 #[pyclass]
 #[text_signature = "(x: float, y: float, z: float, /)"]
@@ -21,11 +24,75 @@ pub struct Camera {
     pub aperture: f64,
     #[pyo3(get, set)]
     pub aspect: f64,
+    /// Radius of the lens, 0 for a pinhole camera
+    #[pyo3(get, set)]
+    pub lens: f64,
+    /// Distance at which objects are sharp, negative to focus on the target
+    #[pyo3(get, set)]
+    pub focus: f64,
+    pub projection: internal::Projection,
+    /// Distance between the eyes, 0 for a single view
+    #[pyo3(get, set)]
+    pub ipd: f64,
+    /// Distance at which the views of both eyes coincide, negative for the target
+    #[pyo3(get, set)]
+    pub convergence: f64,
+    pub mode: internal::Convergence,
+    pub layout: Layout,
+    /// Factor applied to the colors of the image
+    #[pyo3(get, set)]
+    pub exposure: f64,
+    /// Instant at which the shutter opens
+    #[pyo3(get, set)]
+    pub time: f64,
+    /// Duration during which the shutter stays open, 0 for no motion blur
+    #[pyo3(get, set)]
+    pub shutter: f64,
+    /// Displacement of the camera per unit of time
+    pub velocity: internal::Vec3,
 }
 
+fingerprint!(Camera {
+    target,
+    angle,
+    rise,
+    distance,
+    tilt,
+    aperture,
+    aspect,
+    lens,
+    focus,
+    projection,
+    ipd,
+    convergence,
+    mode,
+    layout,
+    exposure,
+    time,
+    shutter,
+    velocity,
+});
+
 impl Camera {
+    /// Aim at the center of the box and move as close as possible while it stays in view
+    fn frame(&mut self, bounds: Option<internal::Aabb>, ratio: f64) -> PyResult<()> {
+        let bounds =
+            bounds.ok_or_else(|| exceptions::ValueError::py_err("Nothing bounded to frame"))?;
+        self.target = (bounds.min + bounds.max) * 0.5;
+        self.distance = internal::Camera::fit_distance(
+            bounds,
+            self.angle,
+            self.rise,
+            self.tilt,
+            self.aperture,
+            ratio,
+            self.projection,
+        );
+        Ok(())
+    }
+
     pub fn to_internal(&self) -> internal::Camera {
-        internal::Camera::new_relative(
+        let mut cam = internal::Camera::new_relative(
             self.target,
             self.angle,
             self.rise,
@@ -33,7 +100,49 @@ impl Camera {
             self.tilt,
             self.aperture,
             self.aspect,
-        )
+            self.projection,
+        );
+        let focus = if self.focus < 0. {
+            None
+        } else {
+            Some(self.focus)
+        };
+        cam.set_lens(self.lens, focus);
+        cam.set_shutter(self.time, self.time + self.shutter);
+        if self.shutter > 0. {
+            // The camera is where it was placed when the shutter opens
+            cam.set_motion(internal::Motion::keyframes(vec![
+                internal::Keyframe::new(
+                    self.time,
+                    internal::Vec3(0., 0., 0.),
+                    internal::Quat::identity(),
+                ),
+                internal::Keyframe::new(
+                    self.time + self.shutter,
+                    self.velocity * self.shutter,
+                    internal::Quat::identity(),
+                ),
+            ]));
+        }
+        cam
+    }
+
+    /// Configuration of the stereo rig, if any
+    pub fn stereo(&self) -> Option<Stereo> {
+        if self.ipd <= 0. {
+            return None;
+        }
+        let convergence = if self.convergence < 0. {
+            self.distance
+        } else {
+            self.convergence
+        };
+        Some(Stereo {
+            ipd: self.ipd,
+            convergence,
+            mode: self.mode,
+            layout: self.layout,
+        })
     }
 }
 
@@ -49,13 +158,123 @@ impl Camera {
             tilt: 0.,
             aperture: 30.,
             aspect: -1.,
+            lens: 0.,
+            focus: -1.,
+            projection: internal::Projection::Perspective,
+            ipd: 0.,
+            convergence: -1.,
+            mode: internal::Convergence::OffAxis,
+            layout: Layout::SideBySide,
+            exposure: 1.,
+            time: 0.,
+            shutter: 0.,
+            velocity: internal::Vec3(0., 0., 0.),
         }
     }
 
+    /// One of "perspective", "orthographic", "fisheye" or "equirectangular"
+    #[getter]
+    pub fn get_projection(&self) -> &str {
+        self.projection.name()
+    }
+
+    #[setter]
+    pub fn set_projection(&mut self, name: &str) -> PyResult<()> {
+        match internal::Projection::from_name(name) {
+            Some(p) => {
+                self.projection = p;
+                Ok(())
+            }
+            None => Err(exceptions::ValueError::py_err(format!(
+                "Unknown projection '{}'",
+                name
+            ))),
+        }
+    }
+
+    /// How the eyes converge, "off-axis" or "toe-in"
+    #[getter]
+    pub fn get_mode(&self) -> &str {
+        self.mode.name()
+    }
+
+    #[setter]
+    pub fn set_mode(&mut self, name: &str) -> PyResult<()> {
+        match internal::Convergence::from_name(name) {
+            Some(m) => {
+                self.mode = m;
+                Ok(())
+            }
+            None => Err(exceptions::ValueError::py_err(format!(
+                "Unknown convergence mode '{}'",
+                name
+            ))),
+        }
+    }
+
+    /// Arrangement of both eyes in the image, "side-by-side" or "top-bottom"
+    #[getter]
+    pub fn get_layout(&self) -> &str {
+        self.layout.name()
+    }
+
+    #[setter]
+    pub fn set_layout(&mut self, name: &str) -> PyResult<()> {
+        match Layout::from_name(name) {
+            Some(l) => {
+                self.layout = l;
+                Ok(())
+            }
+            None => Err(exceptions::ValueError::py_err(format!(
+                "Unknown layout '{}'",
+                name
+            ))),
+        }
+    }
+
+    /// Derive the field of view, aspect ratio, lens and exposure from a real camera
+    #[text_signature = "($self, physical: PhysicalCamera, /)"]
+    pub fn set_physical(&mut self, physical: PhysicalCamera) {
+        let p = physical.to_internal();
+        self.aperture = p.vfov();
+        self.aspect = p.aspect();
+        self.lens = p.lens_radius();
+        self.focus = p.focus.unwrap_or(-1.);
+        self.exposure = p.exposure();
+        self.shutter = p.shutter;
+    }
+
+    #[text_signature = "($self, x: float, y: float, z: float, /)"]
+    pub fn set_velocity(&mut self, x: f64, y: f64, z: f64) {
+        self.velocity = internal::Vec3(x, y, z);
+    }
+
     #[text_signature = "($self, x: float, y: float, z: float, /)"]
     pub fn set_target(&mut self, x: f64, y: f64, z: f64) {
         self.target = internal::Vec3(x, y, z);
     }
+
+    /// Choose the target and distance so that the objects of the scene fill the view,
+    /// keeping the angle, rise, tilt and aperture
+    ///
+    /// Unbounded objects such as planes are left out.
+    #[text_signature = "($self, cfg: Cfg, /)"]
+    pub fn fit(&mut self, cfg: &Cfg) -> PyResult<()> {
+        self.frame(cfg.bounds(), cfg.aspect(self))
+    }
+
+    /// Same as `fit` for a single object, the configuration gives the size of the image
+    #[text_signature = "($self, cfg: Cfg, object: Construct, /)"]
+    pub fn fit_object(&mut self, cfg: &Cfg, object: Construct) -> PyResult<()> {
+        let bounds = internal::world::bounds_of_bounded(&object.contents.canonical());
+        self.frame(bounds, cfg.aspect(self))
+    }
+
+    #[text_signature = "($self, cfg: Cfg, object: Prebuilt, /)"]
+    pub fn fit_prebuilt(&mut self, cfg: &Cfg, object: Prebuilt) -> PyResult<()> {
+        let bounds = internal::world::bounds_of_bounded(&object.extract());
+        self.frame(bounds, cfg.aspect(self))
+    }
 }
 
 #[pyproto]
@@ -70,6 +289,17 @@ impl PyObjectProtocol for Camera {
     tilt:     {},
     aperture: {},
     aspect:   {},
+    lens:     {},
+    focus:    {},
+    projection: {},
+    ipd:      {},
+    convergence: {},
+    mode:     {},
+    layout:   {},
+    exposure: {},
+    time:     {},
+    shutter:  {},
+    velocity: {},
 }}",
             repr!(Vec, self.target),
             self.angle,
@@ -78,6 +308,206 @@ impl PyObjectProtocol for Camera {
             self.tilt,
             self.aperture,
             self.aspect,
+            self.lens,
+            self.focus,
+            self.projection.name(),
+            self.ipd,
+            self.convergence,
+            self.mode.name(),
+            self.layout.name(),
+            self.exposure,
+            self.time,
+            self.shutter,
+            repr!(Vec, self.velocity),
+        ))
+    }
+}
+
+#[pyclass]
+#[text_signature = "(focal: float, /)"]
+#[derive(Clone, Copy)]
+pub struct PhysicalCamera {
+    /// Focal length (mm)
+    #[pyo3(get, set)]
+    pub focal: f64,
+    /// Width of the sensor (mm)
+    #[pyo3(get, set)]
+    pub sensor_width: f64,
+    /// Height of the sensor (mm)
+    #[pyo3(get, set)]
+    pub sensor_height: f64,
+    #[pyo3(get, set)]
+    pub f_number: f64,
+    #[pyo3(get, set)]
+    pub iso: f64,
+    /// Exposure time (s)
+    #[pyo3(get, set)]
+    pub shutter: f64,
+    /// Distance at which objects are sharp, negative to focus on the target
+    #[pyo3(get, set)]
+    pub focus: f64,
+    /// Length of a meter in the units of the scene
+    #[pyo3(get, set)]
+    pub unit: f64,
+}
+
+impl PhysicalCamera {
+    pub fn to_internal(self) -> internal::PhysicalCamera {
+        internal::PhysicalCamera {
+            focal: self.focal,
+            sensor: (self.sensor_width, self.sensor_height),
+            f_number: self.f_number,
+            iso: self.iso,
+            shutter: self.shutter,
+            focus: if self.focus < 0. {
+                None
+            } else {
+                Some(self.focus)
+            },
+            unit: self.unit,
+        }
+    }
+}
+
+#[pymethods]
+impl PhysicalCamera {
+    /// Full frame camera set for the "sunny 16" rule
+    #[new]
+    pub fn new(focal: f64) -> Self {
+        let p = internal::PhysicalCamera::full_frame(focal);
+        Self {
+            focal: p.focal,
+            sensor_width: p.sensor.0,
+            sensor_height: p.sensor.1,
+            f_number: p.f_number,
+            iso: p.iso,
+            shutter: p.shutter,
+            focus: -1.,
+            unit: p.unit,
+        }
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for PhysicalCamera {
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "PhysicalCamera {{
+    focal:   {}mm,
+    sensor:  {}x{}mm,
+    f_number: {},
+    iso:     {},
+    shutter: {}s,
+    focus:   {},
+    unit:    {},
+}}",
+            self.focal,
+            self.sensor_width,
+            self.sensor_height,
+            self.f_number,
+            self.iso,
+            self.shutter,
+            self.focus,
+            self.unit,
         ))
     }
 }
+
+#[pyclass]
+#[text_signature = "(points: list, kind: str, /)"]
+#[derive(Clone)]
+pub struct CameraPath {
+    pub contents: internal::CameraPath,
+}
+
+/// kind is one of "catmull-rom" (through all points) or "bezier" (3n+1 control points)
+fn make_path(points: vec::Vec<Vec>, kind: &str) -> PyResult<internal::Path> {
+    let pts = points
+        .into_iter()
+        .map(|p| p.to_internal())
+        .collect::<vec::Vec<_>>();
+    match kind {
+        "catmull-rom" if pts.len() >= 2 => Ok(internal::Path::catmull_rom(&pts)),
+        "bezier" if pts.len() >= 4 && pts.len() % 3 == 1 => Ok(internal::Path::bezier(&pts)),
+        "catmull-rom" | "bezier" => Err(exceptions::ValueError::py_err(format!(
+            "Not enough points for a {} path",
+            kind
+        ))),
+        _ => Err(exceptions::ValueError::py_err(format!(
+            "Unknown kind of path '{}'",
+            kind
+        ))),
+    }
+}
+
+#[pymethods]
+impl CameraPath {
+    /// Path of the camera, which looks 1 unit ahead along it
+    #[new]
+    pub fn new(points: vec::Vec<Vec>, kind: &str) -> PyResult<Self> {
+        Ok(Self {
+            contents: internal::CameraPath::new(make_path(points, kind)?, internal::Aim::Ahead(1.)),
+        })
+    }
+
+    #[getter]
+    pub fn length(&self) -> f64 {
+        self.contents.path.length()
+    }
+
+    /// Roll into the turns, 0 keeps the horizon level
+    #[getter]
+    pub fn get_bank(&self) -> f64 {
+        self.contents.bank
+    }
+
+    #[setter]
+    pub fn set_bank(&mut self, bank: f64) {
+        self.contents.bank = bank;
+    }
+
+    #[text_signature = "($self, x: float, y: float, z: float, /)"]
+    pub fn look_at(&mut self, x: f64, y: f64, z: f64) {
+        self.contents.aim = internal::Aim::Point(internal::Vec3(x, y, z));
+    }
+
+    /// Look at a target that moves along its own path, at the same speed relative to
+    /// its length
+    #[text_signature = "($self, points: list, kind: str, /)"]
+    pub fn follow(&mut self, points: vec::Vec<Vec>, kind: &str) -> PyResult<()> {
+        self.contents.aim = internal::Aim::Path(make_path(points, kind)?);
+        Ok(())
+    }
+
+    #[text_signature = "($self, distance: float, /)"]
+    pub fn look_ahead(&mut self, distance: f64) {
+        self.contents.aim = internal::Aim::Ahead(distance);
+    }
+
+    /// Camera at fraction s (between 0 and 1) of the length of the path, with the other
+    /// settings of base
+    ///
+    /// The rise of a camera is limited to 90°, which is reached 45° above the target:
+    /// a `ValueError` is raised for steeper views.
+    #[text_signature = "($self, s: float, base: Camera, /)"]
+    pub fn camera(&self, s: f64, base: Camera) -> PyResult<Camera> {
+        let (eye, target, roll) = self.contents.view(s);
+        let d = eye - target;
+        let hyp = d.0.hypot(d.2);
+        if d.1.abs() > hyp * (1. + internal::EPSILON) {
+            return Err(exceptions::ValueError::py_err(format!(
+                "Camera at {} looks at its target more than 45° from above or below",
+                s
+            )));
+        }
+        let sin_rise = if hyp > 0. { d.1 / hyp } else { 0. };
+        Ok(Camera {
+            target,
+            angle: d.0.atan2(d.2).to_degrees(),
+            rise: sin_rise.clamp(-1., 1.).asin().to_degrees(),
+            distance: d.len(),
+            tilt: roll,
+            ..base
+        })
+    }
+}
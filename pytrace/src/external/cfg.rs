@@ -1,10 +1,15 @@
-use glob::glob;
+use pyo3::exceptions;
 use pyo3::prelude::*;
 use pyo3::PyObjectProtocol;
-use std::process::Command;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::vec;
 
+use super::timeline::check_fps;
 use crate::external::*;
 use pytrace_core::internal;
+use pytrace_core::movie;
 use pytrace_core::render::*;
 
 #[pyclass]
@@ -19,16 +24,62 @@ pub struct Cfg {
     pub iter: usize,
     cam: Option<Camera>,
     world: internal::World,
+    /// Digest and bounding box of each object of `world`
+    objects: vec::Vec<(u64, Option<internal::Aabb>)>,
     sky: Option<Sky>,
     mov: Option<MovieCfg>,
     #[pyo3(get, set)]
     nbsync: usize,
+    movie_format: movie::Format,
+    fps: f64,
+    /// Only render again the parts of frames where objects were added or removed
+    ///
+    /// Pixels that first hit a metal or glass surface are always rendered again, but
+    /// shadows and indirect lighting of the changed objects on the rest of the scene
+    /// keep the look of the previous frame.
+    #[pyo3(get, set)]
+    pub partial: bool,
 }
 
 struct MovieCfg {
     name: String,
     cnt: usize,
-    modif: bool,
+    /// First frame that showed each scene, by digest
+    seen: HashMap<u64, usize>,
+    last: Option<Snapshot>,
+}
+
+/// State of the scene when the previous frame was created
+struct Snapshot {
+    /// Digest of everything but the objects
+    setup: u64,
+    objects: vec::Vec<(u64, Option<internal::Aabb>)>,
+}
+
+impl Snapshot {
+    /// Bounding boxes of the objects that were added or removed since, `None` if
+    /// anything else changed or if one of these objects is unbounded
+    fn changes(
+        &self,
+        setup: u64,
+        objects: &[(u64, Option<internal::Aabb>)],
+    ) -> Option<vec::Vec<internal::Aabb>> {
+        if setup != self.setup {
+            return None;
+        }
+        let mut removed = self.objects.clone();
+        let mut changed = vec::Vec::new();
+        for obj in objects {
+            match removed.iter().position(|o| o.0 == obj.0) {
+                Some(i) => {
+                    removed.swap_remove(i);
+                }
+                None => changed.push(obj.1),
+            }
+        }
+        changed.extend(removed.iter().map(|o| o.1));
+        changed.into_iter().collect()
+    }
 }
 
 #[pymethods]
@@ -44,9 +95,13 @@ That's ok, but don't mix them up."));
             iter,
             cam: None,
             world: internal::World::new(),
+            objects: vec::Vec::new(),
             sky: None,
             mov: None,
             nbsync: 5,
+            movie_format: movie::Format::Gif,
+            fps: 25.,
+            partial: false,
         }
     }
 
@@ -56,71 +111,47 @@ That's ok, but don't mix them up."));
     }
 
     #[text_signature = "($self, name: str, /)"]
-    pub fn render(&self, name: String) {
-        if let Some(mut cam) = self.cam {
-            if cam.aspect < 0. {
-                cam.aspect = self.wth as f64 / self.hgt as f64;
-            }
-            if let Some(sky) = &self.sky {
-                render(Builder {
-                    name,
-                    silent: self.silent,
-                    hgt: self.hgt,
-                    wth: self.wth,
-                    iter: self.iter,
-                    cam: cam.to_internal(),
-                    world: self.world.clone(),
-                    sky: sky.to_internal(),
-                    nbsync: self.nbsync,
-                })
-            } else {
-                panic!("No sky provided")
-            }
-        } else {
-            panic!("No camera provided")
-        }
+    pub fn render(&self, py: Python, name: String) {
+        let build = self.builder(name);
+        // Release the GIL, so that the rendering threads can take it back to call the
+        // functions of `Implicit` objects
+        py.allow_threads(move || render(build))
     }
 
     #[text_signature = "($self, r: float, g: float, b: float, /)"]
     pub fn set_background(&mut self, r: f64, g: f64, b: f64) {
         self.world.background = Some(internal::RGB(r, g, b));
-        self.refresh();
     }
 
     #[text_signature = "($self, /)"]
     pub fn true_background(&mut self) {
         self.world.background = None;
-        self.refresh();
     }
 
     #[text_signature = "($self, camera: Camera, /)"]
     pub fn set_cam(&mut self, cam: Camera) {
         self.cam = Some(cam);
-        self.refresh();
     }
 
     #[text_signature = "($self, sky: Sky, /)"]
     pub fn set_sky(&mut self, sky: Sky) {
         self.sky = Some(sky);
-        self.refresh();
     }
 
     #[text_signature = "($self, object: Prebuilt, /)"]
     pub fn populate(&mut self, object: Prebuilt) {
-        self.world.push_vec(object.extract());
-        self.refresh();
+        self.push(object.digest(), object.extract());
     }
 
     #[text_signature = "($self, /)"]
     pub fn clear(&mut self) {
         self.world.clear();
-        self.refresh();
+        self.objects.clear();
     }
 
     #[text_signature = "($self, object: Construct, /)"]
     pub fn add_obj(&mut self, object: Construct) {
-        self.world.push_vec(object.contents.canonical());
-        self.refresh();
+        self.push(object.digest(), object.contents.canonical());
     }
 
     #[text_signature = "($self, name: str, /)"]
@@ -128,73 +159,166 @@ That's ok, but don't mix them up."));
         self.mov = Some(MovieCfg {
             name,
             cnt: 0,
-            modif: true,
+            seen: HashMap::new(),
+            last: None,
         });
     }
 
-    #[text_signature = "($self, name: str, /)"]
-    pub fn frame(&mut self) {
-        if let Some(m) = &self.mov {
+    /// Render the next frame of the movie, or copy the first frame that showed the
+    /// same scene
+    #[text_signature = "($self, /)"]
+    pub fn frame(&mut self, py: Python) {
+        let setup = self.setup();
+        let mut h = DefaultHasher::new();
+        setup.hash(&mut h);
+        for obj in &self.objects {
+            obj.0.hash(&mut h);
+        }
+        let scene = h.finish();
+        let m = self.mov.as_ref().expect("No movie configured");
+        let name = format!("{}-{}", &m.name, m.cnt);
+        if let Some(&k) = m.seen.get(&scene) {
+            println!("Creating frame {} (same as frame {})", m.cnt, k);
+            std::fs::copy(
+                format!("img-{}-{}.ppm", &m.name, k),
+                format!("img-{}.ppm", &name),
+            )
+            .expect("Could not copy previous image");
+        } else {
             println!("Creating frame {}", m.cnt);
-            if m.modif {
-                self.render(format!("{}-{}", &m.name, m.cnt));
-            } else {
-                Command::new("cp")
-                    .arg(&format!("img-{}-{}.ppm", &m.name, m.cnt - 1))
-                    .arg(&format!("img-{}-{}.ppm", &m.name, m.cnt))
-                    .status()
-                    .expect("Could not copy previous image");
+            let mut build = self.builder(name);
+            if self.partial {
+                let changes = m
+                    .last
+                    .as_ref()
+                    .and_then(|l| l.changes(setup, &self.objects));
+                build.patch = changes.map(|changed| Patch {
+                    previous: format!("img-{}-{}.ppm", &m.name, m.cnt - 1),
+                    changed,
+                });
             }
-        } else {
-            panic!("No movie configured");
+            py.allow_threads(move || render(build));
         }
+        let objects = self.objects.clone();
         if let Some(m) = &mut self.mov {
+            m.seen.entry(scene).or_insert(m.cnt);
+            m.last = Some(Snapshot { setup, objects });
             m.cnt += 1;
-            m.modif = false;
         }
     }
 
+    /// One of "gif", "apng", "sequence" (directory of frames) or "ffmpeg" (needs the
+    /// ffmpeg program)
+    #[getter]
+    pub fn get_movie_format(&self) -> &'static str {
+        self.movie_format.name()
+    }
+
+    #[setter]
+    pub fn set_movie_format(&mut self, name: &str) -> PyResult<()> {
+        self.movie_format = movie::Format::from_name(name).ok_or_else(|| {
+            exceptions::ValueError::py_err(format!("Unknown movie format '{}'", name))
+        })?;
+        Ok(())
+    }
+
+    /// Frames per second of the movie
+    #[getter]
+    pub fn get_fps(&self) -> f64 {
+        self.fps
+    }
+
+    #[setter]
+    pub fn set_fps(&mut self, fps: f64) -> PyResult<()> {
+        check_fps(fps)?;
+        self.fps = fps;
+        Ok(())
+    }
+
     #[text_signature = "($self, /)"]
-    pub fn end_movie(&mut self) {
+    pub fn end_movie(&mut self) -> PyResult<()> {
         if let Some(m) = &self.mov {
-            Command::new("rm")
-                .arg(&format!("{}.avi", &m.name))
-                .status()
-                .expect("No file to remove");
-            let e = Command::new("ffmpeg")
-                .arg("-pattern_type")
-                .arg("sequence")
-                .arg("-framerate")
-                .arg("25")
-                .arg("-i")
-                .arg(&format!("img-{}-%d.ppm", &m.name))
-                .arg("-vcodec")
-                .arg("libx264")
-                .arg(&format!("{}.avi", &m.name))
-                .status()
-                .expect("Failed to create movie");
-            if e.success() {
-                println!("Done creating movie, cleanup files");
-                for f in
-                    glob(&format!("img-{}-*.ppm", &m.name)).expect("Could not read glob pattern")
-                {
-                    Command::new("rm")
-                        .arg(&format!("{}", f.unwrap().display()))
-                        .status()
-                        .expect("Failed to remove");
-                }
-                self.mov = None;
+            let frames = (0..m.cnt)
+                .map(|i| format!("img-{}-{}.ppm", &m.name, i))
+                .collect::<std::vec::Vec<_>>();
+            movie::assemble(&frames, &m.name, self.fps, self.movie_format).map_err(|e| {
+                exceptions::IOError::py_err(format!("Failed to create movie: {}", e))
+            })?;
+            println!(
+                "Done creating {}, cleanup files",
+                self.movie_format.output(&m.name)
+            );
+            for f in &frames {
+                std::fs::remove_file(f)?;
             }
+            self.mov = None;
         }
+        Ok(())
     }
 }
 
 impl Cfg {
-    fn refresh(&mut self) {
-        if let Some(m) = &mut self.mov {
-            m.modif = true;
+    /// Settings of the image as they are now
+    pub fn builder(&self, name: String) -> Builder {
+        if let Some(mut cam) = self.cam {
+            let stereo = cam.stereo();
+            cam.aspect = self.aspect(&cam);
+            if let Some(sky) = &self.sky {
+                Builder {
+                    name,
+                    silent: self.silent,
+                    hgt: self.hgt,
+                    wth: self.wth,
+                    iter: self.iter,
+                    cam: cam.to_internal(),
+                    stereo,
+                    exposure: cam.exposure,
+                    world: self.world.clone(),
+                    sky: sky.to_internal(),
+                    nbsync: self.nbsync,
+                    patch: None,
+                }
+            } else {
+                panic!("No sky provided")
+            }
+        } else {
+            panic!("No camera provided")
+        }
+    }
+
+    /// Aspect ratio of the view of the camera, given by the size of the image unless
+    /// the camera has its own
+    pub fn aspect(&self, cam: &Camera) -> f64 {
+        if cam.aspect >= 0. {
+            cam.aspect
+        } else {
+            match cam.stereo() {
+                Some(s) => s.layout.eye_aspect(self.wth, self.hgt),
+                None => self.wth as f64 / self.hgt as f64,
+            }
         }
     }
+
+    /// Box around the bounded objects of the scene
+    pub fn bounds(&self) -> Option<internal::Aabb> {
+        self.world.bounds()
+    }
+
+    fn push(&mut self, digest: u64, obj: internal::Composite) {
+        self.objects
+            .push((digest, internal::world::bounds_of_all(&obj)));
+        self.world.push_vec(obj);
+    }
+
+    /// Digest of the settings, camera, sky and background
+    fn setup(&self) -> u64 {
+        let mut h = DefaultHasher::new();
+        (self.wth, (self.hgt, self.iter)).fingerprint(&mut h);
+        self.cam.fingerprint(&mut h);
+        self.sky.fingerprint(&mut h);
+        self.world.background.fingerprint(&mut h);
+        h.finish()
+    }
 }
 
 #[pyproto]
@@ -207,8 +331,17 @@ impl PyObjectProtocol for Cfg {
     wth:    {},
     iter:   {},
     nbsync: {},
+    movie:  {} at {} fps,
+    partial: {},
 }}",
-            self.silent, self.hgt, self.wth, self.iter, self.nbsync,
+            self.silent,
+            self.hgt,
+            self.wth,
+            self.iter,
+            self.nbsync,
+            self.movie_format.name(),
+            self.fps,
+            self.partial,
         ))
     }
 }
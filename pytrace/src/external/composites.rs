@@ -2,6 +2,7 @@ use crate::external::*;
 use pyo3::prelude::*;
 use pyo3::PyObjectProtocol;
 use pytrace_core::{composite, internal};
+use std::collections::hash_map::DefaultHasher;
 use std::sync::Arc;
 
 #[pyclass]
@@ -10,13 +11,15 @@ pub struct Prebuilt {
     contents: Arc<dyn Develop>,
 }
 
+fingerprint!(Prebuilt { contents });
+
 impl Prebuilt {
     pub fn extract(&self) -> internal::Composite {
         self.contents.develop()
     }
 }
 
-pub trait Develop: Send + Sync {
+pub trait Develop: Send + Sync + Fingerprint {
     fn develop(&self) -> internal::Composite;
 }
 
@@ -43,6 +46,8 @@ impl Axes {
     }
 }
 
+fingerprint!(Axes { scale });
+
 impl Develop for Axes {
     fn develop(&self) -> internal::Composite {
         composite::Axes { scale: self.scale }.build()
@@ -72,6 +77,9 @@ pub struct Cradle {
     pub size: f64,
     pub amplitude: f64,
     pub time: f64,
+    /// Duration over which the motion of the balls is recorded, 0 for still balls
+    #[pyo3(get, set)]
+    pub shutter: f64,
 }
 
 #[pymethods]
@@ -84,6 +92,7 @@ impl Cradle {
             size,
             amplitude: 0.,
             time: 0.,
+            shutter: 0.,
         }
     }
 
@@ -112,8 +121,11 @@ impl Cradle {
 }
 
 impl Cradle {
-    fn calc_balls(&self) -> [f64; 5] {
-        let c = (self.time * 2. * std::f64::consts::PI).sin() * self.amplitude;
+    /// Number of positions recorded while the shutter is open
+    const STEPS: usize = 8;
+
+    fn calc_balls(&self, time: f64) -> [f64; 5] {
+        let c = (time * 2. * std::f64::consts::PI).sin() * self.amplitude;
         if c < 0. {
             [c, 0., 0., 0., 0.]
         } else {
@@ -122,15 +134,34 @@ impl Cradle {
     }
 }
 
+fingerprint!(Cradle {
+    position,
+    rotation,
+    size,
+    amplitude,
+    time,
+    shutter,
+});
+
 impl Develop for Cradle {
     fn develop(&self) -> internal::Composite {
-        composite::NewtonCradle {
+        let cradle = composite::NewtonCradle {
             a: self.position.to_internal(),
             angle: self.rotation,
             size: self.size,
-            pos: Some(self.calc_balls()),
+            pos: Some(self.calc_balls(self.time)),
+        };
+        if self.shutter > 0. {
+            let frames = (0..=Self::STEPS)
+                .map(|k| {
+                    let t = self.time + self.shutter * k as f64 / Self::STEPS as f64;
+                    (t, self.calc_balls(t))
+                })
+                .collect::<std::vec::Vec<_>>();
+            cradle.build_moving(&frames)
+        } else {
+            cradle.build()
         }
-        .build()
     }
 }
 
@@ -193,6 +224,15 @@ impl Die {
     }
 }
 
+fingerprint!(Die {
+    position,
+    direction,
+    rotation,
+    side_texture,
+    edge_texture,
+    dot_texture,
+});
+
 impl Develop for Die {
     fn develop(&self) -> internal::Composite {
         composite::Die {
@@ -261,6 +301,13 @@ impl Molecule {
     }
 }
 
+fingerprint!(Molecule {
+    origin,
+    direction,
+    rotation,
+    structure,
+});
+
 impl Develop for Molecule {
     fn develop(&self) -> internal::Composite {
         let m = composite::Molecule {
@@ -292,6 +339,12 @@ pub enum MoleculeStructure {
     Benzene,
 }
 
+impl Fingerprint for MoleculeStructure {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        (*self as usize).fingerprint(h);
+    }
+}
+
 impl MoleculeStructure {
     pub fn from(s: String) -> Self {
         match &s[..] {
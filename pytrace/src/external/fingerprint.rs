@@ -0,0 +1,169 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::vec;
+
+use pytrace_core::internal;
+use pytrace_core::render::Layout;
+
+/// Hash of the contents of a part of the scene, used to tell whether a frame of a
+/// movie changed since the previous ones
+///
+/// Floats are hashed bit by bit, so values that compare equal but are written
+/// differently (`0.` and `-0.`) count as different.
+pub trait Fingerprint {
+    fn fingerprint(&self, h: &mut DefaultHasher);
+
+    fn digest(&self) -> u64 {
+        let mut h = DefaultHasher::new();
+        self.fingerprint(&mut h);
+        h.finish()
+    }
+}
+
+/// Fingerprint of a struct: its name followed by the listed fields
+macro_rules! fingerprint {
+    ( $name:ty { $( $field:ident ),* $(,)? } ) => {
+        impl crate::external::Fingerprint for $name {
+            fn fingerprint(&self, h: &mut std::collections::hash_map::DefaultHasher) {
+                std::hash::Hash::hash(stringify!($name), h);
+                $( crate::external::Fingerprint::fingerprint(&self.$field, h); )*
+            }
+        }
+    };
+}
+
+impl Fingerprint for f64 {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.to_bits().hash(h);
+    }
+}
+
+impl Fingerprint for usize {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.hash(h);
+    }
+}
+
+impl Fingerprint for bool {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.hash(h);
+    }
+}
+
+impl<A: Fingerprint, B: Fingerprint> Fingerprint for (A, B) {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.0.fingerprint(h);
+        self.1.fingerprint(h);
+    }
+}
+
+impl<T: Fingerprint> Fingerprint for [T] {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.len().hash(h);
+        for x in self {
+            x.fingerprint(h);
+        }
+    }
+}
+
+impl<T: Fingerprint> Fingerprint for [T; 4] {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self[..].fingerprint(h);
+    }
+}
+
+impl<T: Fingerprint> Fingerprint for vec::Vec<T> {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self[..].fingerprint(h);
+    }
+}
+
+impl<T: Fingerprint + ?Sized> Fingerprint for Arc<T> {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        (**self).fingerprint(h);
+    }
+}
+
+impl<T: Fingerprint> Fingerprint for Option<T> {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        match self {
+            None => 0usize.hash(h),
+            Some(x) => {
+                1usize.hash(h);
+                x.fingerprint(h);
+            }
+        }
+    }
+}
+
+impl Fingerprint for internal::Vec3 {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        (self.0, (self.1, self.2)).fingerprint(h);
+    }
+}
+
+impl Fingerprint for internal::RGB {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        (self.0, (self.1, self.2)).fingerprint(h);
+    }
+}
+
+fingerprint!(internal::Quat { w, x, y, z });
+fingerprint!(internal::Transform { matrix });
+fingerprint!(internal::Keyframe {
+    time,
+    translation,
+    rotation,
+    scale,
+});
+
+impl Fingerprint for internal::Mat4 {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        for row in &self.0 {
+            row[..].fingerprint(h);
+        }
+    }
+}
+
+impl Fingerprint for internal::Texture {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        let (kind, color, param) = match self {
+            internal::Texture::Lambertian(c) => (0, c, 0.),
+            internal::Texture::Metal(c, fuzz) => (1, c, *fuzz),
+            internal::Texture::Light(c) => (2, c, 0.),
+            internal::Texture::Dielectric(c, idx) => (3, c, *idx),
+        };
+        (kind as usize, (*color, param)).fingerprint(h);
+    }
+}
+
+impl Fingerprint for internal::Motion {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        match self {
+            internal::Motion::Linear(v) => (0usize, *v).fingerprint(h),
+            internal::Motion::Keyframes(keys) => {
+                1usize.fingerprint(h);
+                keys.fingerprint(h);
+            }
+        }
+    }
+}
+
+impl Fingerprint for internal::Projection {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.name().hash(h);
+    }
+}
+
+impl Fingerprint for internal::Convergence {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.name().hash(h);
+    }
+}
+
+impl Fingerprint for Layout {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        self.name().hash(h);
+    }
+}
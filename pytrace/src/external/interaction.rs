@@ -1,11 +1,16 @@
+use crate::external::{Fingerprint, Quat, Transform, Vec};
 use crate::internal;
+use crate::internal::Transformable;
 use pyo3::prelude::*;
-use pyo3::{PyNumberProtocol, PyObjectProtocol};
+use pyo3::{exceptions, PyNumberProtocol, PyObjectProtocol};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::Hash;
 use std::sync::Arc;
 use std::vec;
 
-pub trait ToInternal: Send + Sync {
+pub trait ToInternal: Send + Sync + Fingerprint {
     fn to_internal(&self) -> internal::Primitive;
     fn display(&self) -> String;
 }
@@ -16,6 +21,8 @@ pub struct Construct {
     pub contents: InterTree,
 }
 
+fingerprint!(Construct { contents });
+
 #[pyproto]
 impl PyObjectProtocol for Construct {
     fn __str__(self) -> PyResult<String> {
@@ -29,6 +36,8 @@ pub struct Primitive {
     pub obj: Arc<dyn ToInternal>,
 }
 
+fingerprint!(Primitive { obj });
+
 impl Primitive {
     pub fn extract(self) -> internal::Interaction {
         self.obj.to_internal().wrap()
@@ -43,6 +52,46 @@ impl Primitive {
     }
 }
 
+/// Any object seen through an affine transformation
+struct Transformed {
+    obj: Arc<dyn ToInternal>,
+    transform: internal::Transform,
+}
+
+fingerprint!(Transformed { obj, transform });
+
+impl ToInternal for Transformed {
+    fn to_internal(&self) -> internal::Primitive {
+        self.obj.to_internal().transform(self.transform)
+    }
+
+    fn display(&self) -> String {
+        format!("<Transformed {}>", self.obj.display())
+    }
+}
+
+/// Any object whose position depends on time
+struct Moving {
+    obj: Arc<dyn ToInternal>,
+    motion: internal::Motion,
+}
+
+fingerprint!(Moving { obj, motion });
+
+impl ToInternal for Moving {
+    fn to_internal(&self) -> internal::Primitive {
+        internal::Moving {
+            obj: self.obj.to_internal(),
+            motion: self.motion.clone(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        format!("<Moving {}>", self.obj.display())
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum Interaction {
     Inter,
@@ -83,60 +132,71 @@ impl InterTree {
         Self::Node(Interaction::Union, Box::new(self), Box::new(other))
     }
 
-    // See explanations below
-    pub fn canonical(&self) -> vec::Vec<internal::Interaction> {
+    /// Give the same motion to every leaf of the tree
+    pub fn moving(&self, motion: &internal::Motion) -> Self {
+        self.map_leaves(
+            &|obj| Moving {
+                obj,
+                motion: motion.clone(),
+            },
+            &mut HashMap::new(),
+        )
+    }
+
+    /// Apply the same transformation to every leaf of the tree
+    pub fn transform(&self, transform: internal::Transform) -> Self {
+        self.map_leaves(&|obj| Transformed { obj, transform }, &mut HashMap::new())
+    }
+
+    /// Wrap every leaf of the tree, once for each distinct object so that repeated
+    /// leaves stay identical
+    fn map_leaves<T: ToInternal + 'static>(
+        &self,
+        f: &dyn Fn(Arc<dyn ToInternal>) -> T,
+        done: &mut HashMap<*const (), Primitive>,
+    ) -> Self {
         match self {
-            Self::Item(p) => vec![p.clone().extract()],
-            Self::Node(inter, a, b) => {
-                let a_can = a.canonical();
-                let b_can = b.canonical();
-                match inter {
-                    Interaction::Union => vec_union(&a_can, &b_can),
-                    Interaction::Inter => {
-                        // (A \ B) & (C \ D) = (A & C) \ (B | D)
-                        let mut res = vec::Vec::new();
-                        for x in &a_can {
-                            for y in &b_can {
-                                let internal::Interaction(x_in, x_out) = x;
-                                let internal::Interaction(y_in, y_out) = y;
-                                res.push(internal::Interaction(
-                                    vec_union(x_in, y_in),
-                                    vec_union(x_out, y_out),
-                                ));
-                            }
-                        }
-                        res
-                    }
-                    Interaction::Diff => {
-                        // (A \ B) \ (C \ D) = (A \ (B | C)) | ((A & D) \ B)
-                        let mut res = a_can;
-                        for y in b_can {
-                            let acc = res;
-                            res = vec::Vec::new();
-                            for x in acc {
-                                let internal::Interaction(x_in, x_out) = &x;
-                                let internal::Interaction(y_in, y_out) = &y;
-                                for z in y_in {
-                                    res.push(internal::Interaction(
-                                        x_in.to_vec(),
-                                        vec_union(&x_out, &[z.clone()]),
-                                    ));
-                                }
-                                if !y_out.is_empty() {
-                                    res.push(internal::Interaction(
-                                        vec_union(&x_in, &y_out),
-                                        x_out.to_vec(),
-                                    ));
-                                }
-                            }
-                        }
-                        res
-                    }
-                }
-            }
+            Self::Item(p) => Self::Item(
+                done.entry(Arc::as_ptr(&p.obj) as *const ())
+                    .or_insert_with(|| Primitive {
+                        obj: Arc::new(f(p.obj.clone())),
+                    })
+                    .clone(),
+            ),
+            Self::Node(inter, a, b) => Self::Node(
+                *inter,
+                Box::new(a.map_leaves(f, done)),
+                Box::new(b.map_leaves(f, done)),
+            ),
+        }
+    }
+
+    /// Same tree with the objects of the library
+    ///
+    /// Repeated leaves are converted once, so that the simplifications of
+    /// `internal::Csg` can tell that they are the same object.
+    pub fn to_csg(&self) -> internal::Csg {
+        self.convert(&mut HashMap::new())
+    }
+
+    fn convert(&self, done: &mut HashMap<*const (), internal::Primitive>) -> internal::Csg {
+        match self {
+            Self::Item(p) => internal::Csg::Item(
+                done.entry(Arc::as_ptr(&p.obj) as *const ())
+                    .or_insert_with(|| p.obj.to_internal())
+                    .clone(),
+            ),
+            Self::Node(Interaction::Inter, a, b) => a.convert(done) & b.convert(done),
+            Self::Node(Interaction::Union, a, b) => a.convert(done) | b.convert(done),
+            Self::Node(Interaction::Diff, a, b) => a.convert(done) - b.convert(done),
         }
     }
 
+    /// Objects ready to be added to the scene, see `internal::Csg::build`
+    pub fn canonical(&self) -> internal::Composite {
+        self.to_csg().build()
+    }
+
     pub fn display(&self) -> String {
         match self {
             Self::Item(p) => p.display(),
@@ -159,15 +219,17 @@ impl InterTree {
     }
 }
 
-fn vec_union<T: Clone>(a: &[T], b: &[T]) -> vec::Vec<T> {
-    let mut res = vec::Vec::new();
-    for x in a {
-        res.push(x.clone());
-    }
-    for x in b {
-        res.push(x.clone());
+impl Fingerprint for InterTree {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        match self {
+            Self::Item(p) => p.fingerprint(h),
+            Self::Node(inter, lt, rt) => {
+                inter.to_string().hash(h);
+                lt.fingerprint(h);
+                rt.fingerprint(h);
+            }
+        }
     }
-    res
 }
 
 #[pymethods]
@@ -189,6 +251,76 @@ impl Construct {
             contents: self.contents.clone().diff(other.contents.clone()),
         }
     }
+
+    /// Move the object by a vector
+    #[text_signature = "($self, offset: Vec, /)"]
+    pub fn translate(&self, offset: Vec) -> Self {
+        Transformable::translate(self.clone(), offset.to_internal())
+    }
+
+    /// Rotate the object by an angle (in degrees) around an axis going through center
+    #[text_signature = "($self, center: Vec, axis: Vec, angle: float, /)"]
+    pub fn rotate(&self, center: Vec, axis: Vec, angle: f64) -> Self {
+        Transformable::rotate(
+            self.clone(),
+            center.to_internal(),
+            axis.to_internal(),
+            angle,
+        )
+    }
+
+    /// Stretch the object along each axis, relative to center
+    #[text_signature = "($self, center: Vec, factors: Vec, /)"]
+    pub fn scale(&self, center: Vec, factors: Vec) -> Self {
+        Transformable::scale(self.clone(), center.to_internal(), factors.to_internal())
+    }
+
+    /// Apply an arbitrary transformation to the object
+    #[text_signature = "($self, t: Transform, /)"]
+    pub fn transform(&self, t: &Transform) -> Self {
+        Transformable::transform(self.clone(), t.to_internal())
+    }
+
+    /// Move the object at constant speed: it is translated by `velocity * time`
+    #[text_signature = "($self, velocity: Vec, /)"]
+    pub fn with_velocity(&self, velocity: Vec) -> Self {
+        self.contents
+            .moving(&internal::Motion::Linear(velocity.to_internal()))
+            .wrap()
+    }
+
+    /// Move the object through keyframes `(time, translation, rotation)`, where the
+    /// rotation is around the origin and applied before the translation
+    #[text_signature = "($self, keys: list, /)"]
+    pub fn animate(&self, keys: vec::Vec<(f64, Vec, Quat)>) -> PyResult<Self> {
+        if keys.is_empty() {
+            return Err(exceptions::ValueError::py_err(
+                "A motion needs at least one keyframe",
+            ));
+        }
+        if let Some((t, _, _)) = keys.iter().find(|k| !k.0.is_finite()) {
+            return Err(exceptions::ValueError::py_err(format!(
+                "Keyframe time {} is not a finite number",
+                t
+            )));
+        }
+        let keys = keys
+            .into_iter()
+            .map(|(t, v, q)| internal::Keyframe::new(t, v.to_internal(), q.to_internal()))
+            .collect();
+        Ok(self
+            .contents
+            .moving(&internal::Motion::keyframes(keys))
+            .wrap())
+    }
+}
+
+impl Transformable for Construct {
+    fn transform(self, t: internal::Transform) -> Self {
+        Self {
+            contents: self.contents.transform(t),
+        }
+    }
 }
 
 #[pyproto]
@@ -205,41 +337,3 @@ impl PyNumberProtocol for Construct {
         Ok(lhs.diff(&rhs))
     }
 }
-
-// About the InterTree::canonical() function
-//
-// The internal algorithm only manages complex objects expressed as :
-// Union_i ( Inter_j (A_i,j) \ Union_k (B_i,k) )
-// This corresponds to a collection (i.e. union) of [Vec, Vec],
-// where the first (resp. second) Vec represents all objects inside
-// (resp. outside) of which we need to be.
-// The user of the Python library need not be aware of this restriction,
-// as the interface allows arbitrary set operations.
-// This is done using a tree (an InterTree) of operations, which is then
-// converted into a canonical representation before being pushed to the
-// scene.
-//
-// The process of translating InterTree -> Vec<[Vec, Vec]> is done recursively
-// by InterTree::canonical()
-// It relies on the following :
-// (&, |, \ represent intersection, union, difference)
-// * a leaf is already under canonical representation :
-//        A -> { [A, ()] }
-// * an union is easy to canonicalize
-//        X | Y -> { X, Y }
-// * A\B & C\D = A&C \ B|D
-//        [A, B] & [C, D] -> [(A.., C..), (B.., D..)]
-// * & is distributive on |
-//        { [A, B], [A', B'] } & { [C, D], [C', D'] }
-//     -> {
-//             [(A.., C..), (B.., D..)],
-//             [(A'.., C..), (B'.., D..)],
-//             [(A.., C'..), (B.., D'..)],
-//             [(A'.., C'..), (B'.., D'..)],
-//        }
-// And finally:
-// * { X, Y } \ { Z, W } = { X\Z\W, Y\Z\W }
-// * [A, B] \ [C, ()] = [A, (B.., C..)]
-// * [A, B] \ [C, D] = { [A, (B.., C..)], [(A.., D..), B] }
-// These three rules are applied by iteratively removing all elements
-// of b_can at each step. The result becomes the basis of the next step.
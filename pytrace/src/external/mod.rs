@@ -7,6 +7,9 @@ macro_rules! repr {
     };
 }
 
+#[macro_use]
+mod fingerprint;
+
 mod camera;
 mod cfg;
 mod composites;
@@ -14,13 +17,18 @@ mod interaction;
 mod primitives;
 mod sky;
 mod texture;
+mod timeline;
+mod transform;
 mod vec;
 
-pub use camera::Camera;
+pub use camera::{Camera, CameraPath, PhysicalCamera};
 pub use cfg::Cfg;
 pub use composites::{Axes, Cradle, Prebuilt};
+pub use fingerprint::Fingerprint;
 pub use interaction::{Construct, Primitive, ToInternal};
 pub use primitives::*;
 pub use sky::Sky;
 pub use texture::{Texture, RGB};
+pub use timeline::Timeline;
+pub use transform::{Mat3, Mat4, Quat, Transform};
 pub use vec::Vec;
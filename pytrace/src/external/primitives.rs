@@ -1,13 +1,21 @@
 use crate::external::*;
 use pyo3::prelude::*;
-use pyo3::PyObjectProtocol;
+use pyo3::{exceptions, PyObjectProtocol};
 use pytrace_core::internal;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::vec;
 
 macro_rules! internalize {
     ( $caller:ident, $member:ident, f64 ) => {
         $caller.$member
     };
+    ( $caller:ident, $member:ident, usize ) => {
+        $caller.$member
+    };
     ( $caller:ident, $member:ident, $t:tt ) => {
         $caller.$member.to_internal()
     };
@@ -17,6 +25,9 @@ macro_rules! printable {
     ( $caller:ident, $item:ident, f64 ) => {
         $caller.$item
     };
+    ( $caller:ident, $item:ident, usize ) => {
+        $caller.$item
+    };
     ( $caller:ident, $item:ident, $t:tt ) => {
         $caller.$item.__repr__().ok().unwrap()
     };
@@ -44,6 +55,8 @@ macro_rules! dvp {
                 pub texture: Texture,
             }
 
+            fingerprint!($name { $( $member, )* texture });
+
             #[pymethods]
             impl $name {
                 #[new]
@@ -178,4 +191,807 @@ dvp! {
         begin: f64 (begin),
         end: f64 (end),
     };
+
+    #[sig="(center: Vec, axis: Vec, major: float, minor: float, texture: Texture, /)"]
+    #[repr="Torus({}, {}, {}, {})"]
+    #[str="<Torus object at {} with axis {}, radius {} and thickness {}>"]
+    Torus {
+        center: Vec (center),
+        axis: Vec (axis),
+        major: f64 (major),
+        minor: f64 (minor),
+    };
+
+    #[sig="(center: Vec, radii: Vec, texture: Texture, /)"]
+    #[repr="Ellipsoid({}, {})"]
+    #[str="<Ellipsoid object at {} with radii {}>"]
+    Ellipsoid {
+        center: Vec (center),
+        radii: Vec (radii),
+    };
+
+    #[sig="(vertex: Vec, radius: float, height: float, texture: Texture, /)"]
+    #[repr="Paraboloid({}, {}, {})"]
+    #[str="<Paraboloid object at {} with radius {} and height {}>"]
+    Paraboloid {
+        vertex: Vec (vertex),
+        radius: f64 (radius),
+        height: f64 (height),
+    };
+}
+
+#[pyclass]
+#[derive(Copy, Clone)]
+#[text_signature = "(center: Vec, radius: float, scale: float, height: float, sheets: int, texture: Texture, /)"]
+pub struct Hyperboloid {
+    pub center: Vec,
+    pub radius: f64,
+    pub scale: f64,
+    pub height: f64,
+    /// Either 1 or 2
+    pub sheets: usize,
+    pub texture: Texture,
+}
+
+#[pymethods]
+impl Hyperboloid {
+    #[new]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        center: Vec,
+        radius: f64,
+        scale: f64,
+        height: f64,
+        sheets: usize,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        if sheets != 1 && sheets != 2 {
+            return Err(exceptions::ValueError::py_err(format!(
+                "A hyperboloid has 1 or 2 sheets, got {}",
+                sheets
+            )));
+        }
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                center,
+                radius,
+                scale,
+                height,
+                sheets,
+                texture,
+            }),
+        }
+        .wrap())
+    }
+}
+
+fingerprint!(Hyperboloid {
+    center,
+    radius,
+    scale,
+    height,
+    sheets,
+    texture,
+});
+
+impl ToInternal for Hyperboloid {
+    fn to_internal(&self) -> internal::Primitive {
+        internal::Hyperboloid {
+            center: self.center.to_internal(),
+            radius: self.radius,
+            scale: self.scale,
+            height: self.height,
+            two_sheets: self.sheets == 2,
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        self.__str__().ok().unwrap()
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Hyperboloid {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "Hyperboloid({}, {}, {}, {}, {})",
+            repr!(self.center),
+            self.radius,
+            self.scale,
+            self.height,
+            self.sheets
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "<Hyperboloid object at {} with radius {}, scale {}, half-height {} and {} sheet(s)>",
+            repr!(self.center),
+            self.radius,
+            self.scale,
+            self.height,
+            self.sheets
+        ))
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+#[text_signature = "(func: Callable[[float, float, float], float], bounds: Tuple[Vec, Vec], texture: Texture, resolution: int = 200, /)"]
+pub struct Implicit {
+    /// Python callable, negative inside of the object
+    pub func: Arc<PyObject>,
+    pub bounds: (Vec, Vec),
+    pub texture: Texture,
+    /// Number of samples along the diagonal of the bounds when looking for the surface
+    pub resolution: usize,
+    /// Unique to each constructed object, see the fingerprint
+    id: usize,
+}
+
+/// Source of the `id` of `Implicit` objects
+static NEXT_IMPLICIT: AtomicUsize = AtomicUsize::new(0);
+
+#[pymethods]
+impl Implicit {
+    #[new]
+    #[args(resolution = "200")]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        func: PyObject,
+        bounds: (Vec, Vec),
+        texture: Texture,
+        resolution: usize,
+    ) -> PyResult<Construct> {
+        if resolution == 0 {
+            return Err(exceptions::ValueError::py_err(
+                "The resolution of an implicit surface should be positive",
+            ));
+        }
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                func: Arc::new(func),
+                bounds,
+                texture,
+                resolution,
+                id: NEXT_IMPLICIT.fetch_add(1, Ordering::Relaxed),
+            }),
+        }
+        .wrap())
+    }
+}
+
+impl Fingerprint for Implicit {
+    /// Functions cannot be compared, two objects are only the same if they come from
+    /// the same call to the constructor
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        "Implicit".hash(h);
+        self.id.hash(h);
+        (self.bounds, (self.texture, self.resolution)).fingerprint(h);
+    }
+}
+
+impl ToInternal for Implicit {
+    fn to_internal(&self) -> internal::Primitive {
+        let func = self.func.clone();
+        internal::Implicit {
+            // The GIL is only held for the duration of a single call, so that
+            // the rendering threads can take turns evaluating the function.
+            func: Arc::new(move |p: internal::Vec3| {
+                let gil = Python::acquire_gil();
+                let py = gil.python();
+                match func
+                    .call1(py, (p.0, p.1, p.2))
+                    .and_then(|x| x.extract::<f64>(py))
+                {
+                    Ok(x) => x,
+                    Err(e) => {
+                        e.print(py);
+                        f64::NAN
+                    }
+                }
+            }),
+            bounds: internal::Aabb {
+                min: self.bounds.0.to_internal(),
+                max: self.bounds.1.to_internal(),
+            },
+            resolution: self.resolution,
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        format!(
+            "<Implicit object between {} and {}>",
+            repr!(self.bounds.0),
+            repr!(self.bounds.1)
+        )
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Implicit {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "Implicit({}, {})",
+            repr!(self.bounds.0),
+            repr!(self.bounds.1)
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(self.display())
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+#[text_signature = "(position: Vec, width: float, depth: float, height: float, grid: List[List[float]], texture: Texture, /)"]
+pub struct HeightField {
+    pub position: Vec,
+    pub width: f64,
+    pub depth: f64,
+    pub height: f64,
+    /// Altitudes between 0 and 1, one row per value of z
+    pub grid: Arc<vec::Vec<vec::Vec<f64>>>,
+    pub texture: Texture,
+}
+
+#[pymethods]
+impl HeightField {
+    #[new]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        position: Vec,
+        width: f64,
+        depth: f64,
+        height: f64,
+        grid: vec::Vec<vec::Vec<f64>>,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        let cols = grid.first().map_or(0, |r| r.len());
+        if grid.len() < 2 || cols < 2 || grid.iter().any(|r| r.len() != cols) {
+            return Err(exceptions::ValueError::py_err(
+                "A height field needs a grid of at least 2x2 values with rows of the same length",
+            ));
+        }
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                position,
+                width,
+                depth,
+                height,
+                grid: Arc::new(grid),
+                texture,
+            }),
+        }
+        .wrap())
+    }
+
+    /// Read the altitudes from a grayscale PGM (or PPM) image
+    #[staticmethod]
+    #[text_signature = "(file: str, position: Vec, width: float, depth: float, height: float, texture: Texture, /)"]
+    pub fn load(
+        file: &str,
+        position: Vec,
+        width: f64,
+        depth: f64,
+        height: f64,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        let hf = internal::HeightField::load(
+            file,
+            position.to_internal(),
+            width,
+            depth,
+            height,
+            texture.to_internal(),
+        )
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => exceptions::ValueError::py_err(e.to_string()),
+            _ => exceptions::IOError::py_err(e.to_string()),
+        })?;
+        Self::new(position, width, depth, height, hf.grid, texture)
+    }
+}
+
+fingerprint!(HeightField {
+    position,
+    width,
+    depth,
+    height,
+    grid,
+    texture,
+});
+
+impl ToInternal for HeightField {
+    fn to_internal(&self) -> internal::Primitive {
+        internal::HeightField {
+            orig: self.position.to_internal(),
+            wth: self.width,
+            dep: self.depth,
+            hgt: self.height,
+            grid: self.grid.to_vec(),
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        self.__str__().ok().unwrap()
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for HeightField {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "HeightField({}, {}, {}, {})",
+            repr!(self.position),
+            self.width,
+            self.depth,
+            self.height
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "<HeightField object at {} of size {}x{}x{} with {}x{} points>",
+            repr!(self.position),
+            self.width,
+            self.depth,
+            self.height,
+            self.grid.first().map_or(0, |r| r.len()),
+            self.grid.len(),
+        ))
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+#[text_signature = "(base: Vec, axis: Vec, profile: List[Tuple[float, float]], texture: Texture, /)"]
+pub struct Lathe {
+    pub base: Vec,
+    pub axis: Vec,
+    /// Points (radius, height) of the silhouette
+    pub profile: Arc<vec::Vec<(f64, f64)>>,
+    pub texture: Texture,
+}
+
+#[pymethods]
+impl Lathe {
+    #[new]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        base: Vec,
+        axis: Vec,
+        profile: vec::Vec<(f64, f64)>,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        Self::check(axis, &profile)?;
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                base,
+                axis,
+                profile: Arc::new(profile),
+                texture,
+            }),
+        }
+        .wrap())
+    }
+
+    /// Revolve a smooth curve that goes through all of the points
+    #[staticmethod]
+    #[text_signature = "(base: Vec, axis: Vec, points: List[Tuple[float, float]], subdivisions: int, texture: Texture, /)"]
+    pub fn spline(
+        base: Vec,
+        axis: Vec,
+        points: vec::Vec<(f64, f64)>,
+        subdivisions: usize,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        Self::check(axis, &points)?;
+        let lathe = internal::Lathe::spline(
+            base.to_internal(),
+            axis.to_internal(),
+            &points,
+            subdivisions,
+            texture.to_internal(),
+        );
+        Self::new(base, axis, lathe.profile, texture)
+    }
+}
+
+impl Lathe {
+    fn check(axis: Vec, profile: &[(f64, f64)]) -> PyResult<()> {
+        if profile.len() < 2 {
+            return Err(exceptions::ValueError::py_err(
+                "A lathe needs at least two points in its profile",
+            ));
+        }
+        let len = axis.to_internal().len();
+        if len == 0. || !len.is_finite() {
+            return Err(exceptions::ValueError::py_err(
+                "The axis of a lathe should not be zero",
+            ));
+        }
+        if profile.iter().any(|&(r, _)| r < 0. || r.is_nan()) {
+            return Err(exceptions::ValueError::py_err(
+                "The radii of the profile of a lathe should not be negative",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fingerprint!(Lathe {
+    base,
+    axis,
+    profile,
+    texture,
+});
+
+impl ToInternal for Lathe {
+    fn to_internal(&self) -> internal::Primitive {
+        internal::Lathe {
+            base: self.base.to_internal(),
+            axis: self.axis.to_internal(),
+            profile: self.profile.to_vec(),
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        self.__str__().ok().unwrap()
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Lathe {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "Lathe({}, {}, {:?})",
+            repr!(self.base),
+            repr!(self.axis),
+            self.profile
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "<Lathe object at {} along {} with {} points>",
+            repr!(self.base),
+            repr!(self.axis),
+            self.profile.len()
+        ))
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+#[text_signature = "(origin: Vec, u: Vec, v: Vec, depth: Vec, outline: List[Tuple[float, float]], holes: List[List[Tuple[float, float]]], texture: Texture, /)"]
+pub struct Extrusion {
+    pub origin: Vec,
+    pub u: Vec,
+    pub v: Vec,
+    pub depth: Vec,
+    /// Points (x, y) of the polygon, in the basis (u, v)
+    pub outline: Arc<vec::Vec<(f64, f64)>>,
+    pub holes: Arc<vec::Vec<vec::Vec<(f64, f64)>>>,
+    pub texture: Texture,
+}
+
+#[pymethods]
+impl Extrusion {
+    #[new]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        origin: Vec,
+        u: Vec,
+        v: Vec,
+        depth: Vec,
+        outline: vec::Vec<(f64, f64)>,
+        holes: vec::Vec<vec::Vec<(f64, f64)>>,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        let (iu, iv, iw) = (u.to_internal(), v.to_internal(), depth.to_internal());
+        // The volume spanned by the three vectors, relative to their lengths
+        let det = iu.cross(iv).dot(iw);
+        if det.is_nan() || det.abs() <= internal::EPSILON * iu.len() * iv.len() * iw.len() {
+            return Err(exceptions::ValueError::py_err(
+                "The depth of an extrusion should not lie in the plane of u and v",
+            ));
+        }
+        if outline.len() < 3 || holes.iter().any(|h| h.len() < 3) {
+            return Err(exceptions::ValueError::py_err(
+                "The outline and the holes of an extrusion need at least three points",
+            ));
+        }
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                origin,
+                u,
+                v,
+                depth,
+                outline: Arc::new(outline),
+                holes: Arc::new(holes),
+                texture,
+            }),
+        }
+        .wrap())
+    }
+}
+
+fingerprint!(Extrusion {
+    origin,
+    u,
+    v,
+    depth,
+    outline,
+    holes,
+    texture,
+});
+
+impl ToInternal for Extrusion {
+    fn to_internal(&self) -> internal::Primitive {
+        internal::Extrusion {
+            orig: self.origin.to_internal(),
+            u: self.u.to_internal(),
+            v: self.v.to_internal(),
+            w: self.depth.to_internal(),
+            outline: self.outline.to_vec(),
+            holes: self.holes.to_vec(),
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        self.__str__().ok().unwrap()
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Extrusion {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "Extrusion({}, {}, {}, {}, {:?}, {:?})",
+            repr!(self.origin),
+            repr!(self.u),
+            repr!(self.v),
+            repr!(self.depth),
+            self.outline,
+            self.holes
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "<Extrusion object at {} along {} with {} points and {} holes>",
+            repr!(self.origin),
+            repr!(self.depth),
+            self.outline.len(),
+            self.holes.len()
+        ))
+    }
+}
+
+/// Maximum distance between curved surfaces and the triangles that approximate them
+const TOLERANCE: f64 = 0.001;
+
+#[pyclass]
+#[derive(Copy, Clone)]
+#[text_signature = "(control: List[List[Vec]], texture: Texture, /)"]
+pub struct BezierPatch {
+    /// 4 rows of 4 control points
+    pub control: [[Vec; 4]; 4],
+    pub texture: Texture,
+}
+
+#[pymethods]
+impl BezierPatch {
+    #[new]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(control: vec::Vec<vec::Vec<Vec>>, texture: Texture) -> PyResult<Construct> {
+        if control.len() != 4 || control.iter().any(|row| row.len() != 4) {
+            return Err(exceptions::ValueError::py_err(
+                "A Bézier patch needs 4x4 control points",
+            ));
+        }
+        let mut grid = [[Vec::new(0., 0., 0.); 4]; 4];
+        for (i, row) in control.iter().enumerate() {
+            grid[i].copy_from_slice(row);
+        }
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                control: grid,
+                texture,
+            }),
+        }
+        .wrap())
+    }
+}
+
+fingerprint!(BezierPatch { control, texture });
+
+impl ToInternal for BezierPatch {
+    fn to_internal(&self) -> internal::Primitive {
+        let mut control = [[internal::Vec3(0., 0., 0.); 4]; 4];
+        for (i, row) in self.control.iter().enumerate() {
+            for (j, p) in row.iter().enumerate() {
+                control[i][j] = p.to_internal();
+            }
+        }
+        internal::BezierPatch {
+            control,
+            tolerance: TOLERANCE,
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        self.__str__().ok().unwrap()
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for BezierPatch {
+    fn __repr__(self) -> PyResult<String> {
+        let rows = self
+            .control
+            .iter()
+            .map(|row| {
+                let pts = row.iter().map(|p| repr!(p)).collect::<vec::Vec<_>>();
+                format!("[{}]", pts.join(", "))
+            })
+            .collect::<vec::Vec<_>>();
+        Ok(format!("BezierPatch([{}])", rows.join(", ")))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "<BezierPatch object from {} to {}>",
+            repr!(self.control[0][0]),
+            repr!(self.control[3][3])
+        ))
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+#[text_signature = "(points: List[Vec], radii: List[float], texture: Texture, /)"]
+pub struct Tube {
+    pub points: Arc<vec::Vec<Vec>>,
+    pub radii: Arc<vec::Vec<f64>>,
+    /// Chain of Bézier curves instead of a curve through the points
+    pub bezier: bool,
+    pub texture: Texture,
+}
+
+#[pymethods]
+impl Tube {
+    /// Tube along a smooth curve that goes through all of the points
+    #[new]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        points: vec::Vec<Vec>,
+        radii: vec::Vec<f64>,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        Self::make(points, radii, false, texture)
+    }
+
+    /// Tube along a chain of Bézier curves: a start point, then two control points
+    /// and an end point for each piece
+    #[staticmethod]
+    #[text_signature = "(points: List[Vec], radii: List[float], texture: Texture, /)"]
+    pub fn bezier(
+        points: vec::Vec<Vec>,
+        radii: vec::Vec<f64>,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        Self::make(points, radii, true, texture)
+    }
+}
+
+impl Tube {
+    fn make(
+        points: vec::Vec<Vec>,
+        radii: vec::Vec<f64>,
+        bezier: bool,
+        texture: Texture,
+    ) -> PyResult<Construct> {
+        if points.len() != radii.len() {
+            return Err(exceptions::ValueError::py_err(
+                "There should be one radius per point",
+            ));
+        }
+        if points.len() < 2 {
+            return Err(exceptions::ValueError::py_err(
+                "A tube needs at least two points",
+            ));
+        }
+        if bezier && points.len() % 3 != 1 {
+            return Err(exceptions::ValueError::py_err(format!(
+                "A Bézier chain needs 3k+1 points, got {}",
+                points.len()
+            )));
+        }
+        // Ends of each piece of the curve, which must be apart for the direction of the
+        // tube to be defined everywhere
+        let step = if bezier { 3 } else { 1 };
+        if (step..points.len()).step_by(step).any(|i| {
+            (points[i].to_internal() - points[i - step].to_internal()).len() < internal::EPSILON
+        }) {
+            return Err(exceptions::ValueError::py_err(
+                "The pieces of a tube should not start and end at the same point",
+            ));
+        }
+        Ok(Primitive {
+            obj: Arc::new(Self {
+                points: Arc::new(points),
+                radii: Arc::new(radii),
+                bezier,
+                texture,
+            }),
+        }
+        .wrap())
+    }
+}
+
+fingerprint!(Tube {
+    points,
+    radii,
+    bezier,
+    texture,
+});
+
+impl ToInternal for Tube {
+    fn to_internal(&self) -> internal::Primitive {
+        internal::Tube {
+            points: self.points.iter().map(|p| p.to_internal()).collect(),
+            radii: self.radii.to_vec(),
+            curve: if self.bezier {
+                internal::Curve::Bezier
+            } else {
+                internal::Curve::CatmullRom
+            },
+            tolerance: TOLERANCE,
+            texture: self.texture.to_internal(),
+        }
+        .build()
+    }
+
+    fn display(&self) -> String {
+        self.__str__().ok().unwrap()
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Tube {
+    fn __repr__(self) -> PyResult<String> {
+        let pts = self
+            .points
+            .iter()
+            .map(|p| repr!(p))
+            .collect::<vec::Vec<_>>();
+        Ok(format!(
+            "Tube{}([{}], {:?})",
+            if self.bezier { ".bezier" } else { "" },
+            pts.join(", "),
+            self.radii
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "<Tube object through {} points>",
+            self.points.len()
+        ))
+    }
 }
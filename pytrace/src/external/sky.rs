@@ -1,11 +1,22 @@
 use pytrace_core::internal;
 
+use crate::external::Fingerprint;
 use pyo3::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
 
 #[pyclass]
 #[derive(Clone)]
 pub struct Sky {
     contents: internal::Sky,
+    source: Source,
+}
+
+/// Where the sky comes from, to compare skies without looking at all of their pixels
+#[derive(Clone)]
+enum Source {
+    File(String),
+    Uniform(f64, f64, f64),
 }
 
 #[pymethods]
@@ -14,6 +25,7 @@ impl Sky {
     pub fn new(file: &str) -> Self {
         Self {
             contents: internal::Sky::new(file),
+            source: Source::File(file.to_string()),
         }
     }
 
@@ -22,6 +34,7 @@ impl Sky {
     pub fn uniform(r: f64, g: f64, b: f64) -> Self {
         Self {
             contents: internal::Sky::uniform(internal::RGB(r, g, b)),
+            source: Source::Uniform(r, g, b),
         }
     }
 }
@@ -31,3 +44,12 @@ impl Sky {
         self.contents.clone()
     }
 }
+
+impl Fingerprint for Sky {
+    fn fingerprint(&self, h: &mut DefaultHasher) {
+        match &self.source {
+            Source::File(file) => (0, file).hash(h),
+            Source::Uniform(r, g, b) => (1usize, (*r, (*g, *b))).fingerprint(h),
+        }
+    }
+}
@@ -12,6 +12,8 @@ pub struct RGB {
     pub b: f64,
 }
 
+fingerprint!(RGB { r, g, b });
+
 #[pymethods]
 impl RGB {
     #[new]
@@ -83,6 +85,8 @@ pub struct Texture {
     contents: internal::Texture,
 }
 
+fingerprint!(Texture { contents });
+
 #[pyproto]
 impl PyObjectProtocol for Texture {
     fn __str__(self) -> PyResult<String> {
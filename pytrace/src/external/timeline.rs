@@ -0,0 +1,192 @@
+use pyo3::exceptions;
+use pyo3::prelude::*;
+use pyo3::PyObjectProtocol;
+
+use crate::external::*;
+use pytrace_core::internal;
+
+#[pyclass]
+#[text_signature = "(fps: float, /)"]
+#[derive(Clone)]
+pub struct Timeline {
+    pub contents: internal::Timeline,
+}
+
+fn interpolation(name: &str) -> PyResult<internal::Interpolation> {
+    internal::Interpolation::from_name(name)
+        .ok_or_else(|| exceptions::ValueError::py_err(format!("Unknown interpolation '{}'", name)))
+}
+
+/// Frame rates must be positive for the instants of the frames to be defined
+pub(crate) fn check_fps(fps: f64) -> PyResult<()> {
+    if fps > 0. && fps.is_finite() {
+        Ok(())
+    } else {
+        Err(exceptions::ValueError::py_err(format!(
+            "The frame rate should be positive, got {}",
+            fps
+        )))
+    }
+}
+
+impl Timeline {
+    fn object(&mut self, idx: usize) -> PyResult<&mut internal::Animated> {
+        self.contents
+            .objects
+            .get_mut(idx)
+            .ok_or_else(|| exceptions::IndexError::py_err(format!("No object {}", idx)))
+    }
+}
+
+#[pymethods]
+impl Timeline {
+    #[new]
+    pub fn new(fps: f64) -> PyResult<Self> {
+        check_fps(fps)?;
+        Ok(Self {
+            contents: internal::Timeline::new(fps),
+        })
+    }
+
+    /// Frames per unit of time
+    #[getter]
+    pub fn get_fps(&self) -> f64 {
+        self.contents.fps
+    }
+
+    #[setter]
+    pub fn set_fps(&mut self, fps: f64) -> PyResult<()> {
+        check_fps(fps)?;
+        self.contents.fps = fps;
+        Ok(())
+    }
+
+    /// Duration during which the shutter stays open for each frame
+    #[getter]
+    pub fn get_shutter(&self) -> f64 {
+        self.contents.shutter
+    }
+
+    #[setter]
+    pub fn set_shutter(&mut self, shutter: f64) {
+        self.contents.shutter = shutter;
+    }
+
+    /// Add an object and return its index for the keys
+    #[text_signature = "($self, object: Construct, /)"]
+    pub fn add(&mut self, object: Construct) -> usize {
+        self.contents.add(object.contents.canonical())
+    }
+
+    #[text_signature = "($self, object: Prebuilt, /)"]
+    pub fn add_prebuilt(&mut self, object: Prebuilt) -> usize {
+        self.contents.add(object.extract())
+    }
+
+    /// Position, orientation, field of view and lens radius of the camera, the other
+    /// settings are those of the camera of the configuration, except its velocity which
+    /// is ignored
+    ///
+    /// interp is one of "step", "linear" or "bezier"
+    #[text_signature = "($self, time: float, camera: Camera, interp: str, /)"]
+    pub fn camera_key(&mut self, time: f64, camera: Camera, interp: &str) -> PyResult<()> {
+        let key = internal::CameraKey {
+            target: camera.target,
+            angle: camera.angle,
+            rise: camera.rise,
+            distance: camera.distance,
+            tilt: camera.tilt,
+            aperture: camera.aperture,
+            lens: camera.lens,
+        };
+        self.contents.camera.key(time, key, interpolation(interp)?);
+        Ok(())
+    }
+
+    #[text_signature = "($self, object: int, time: float, offset: Vec, interp: str, /)"]
+    pub fn translation_key(
+        &mut self,
+        object: usize,
+        time: f64,
+        offset: Vec,
+        interp: &str,
+    ) -> PyResult<()> {
+        let interp = interpolation(interp)?;
+        self.object(object)?
+            .translation
+            .key(time, offset.to_internal(), interp);
+        Ok(())
+    }
+
+    /// Rotation around the origin, "linear" and "slerp" are the same for rotations
+    #[text_signature = "($self, object: int, time: float, rotation: Quat, interp: str, /)"]
+    pub fn rotation_key(
+        &mut self,
+        object: usize,
+        time: f64,
+        rotation: Quat,
+        interp: &str,
+    ) -> PyResult<()> {
+        let interp = interpolation(interp)?;
+        self.object(object)?
+            .rotation
+            .key(time, rotation.to_internal(), interp);
+        Ok(())
+    }
+
+    #[text_signature = "($self, object: int, time: float, factors: Vec, interp: str, /)"]
+    pub fn scale_key(
+        &mut self,
+        object: usize,
+        time: f64,
+        factors: Vec,
+        interp: &str,
+    ) -> PyResult<()> {
+        let interp = interpolation(interp)?;
+        self.object(object)?
+            .scale
+            .key(time, factors.to_internal(), interp);
+        Ok(())
+    }
+
+    /// Color that replaces the one of all textures of the object
+    #[text_signature = "($self, object: int, time: float, color: RGB, interp: str, /)"]
+    pub fn color_key(
+        &mut self,
+        object: usize,
+        time: f64,
+        color: RGB,
+        interp: &str,
+    ) -> PyResult<()> {
+        let interp = interpolation(interp)?;
+        self.object(object)?
+            .color
+            .key(time, color.to_internal(), interp);
+        Ok(())
+    }
+
+    /// Render frames first to last (excluded) as img-{name}-{frame}.ppm, on top of the
+    /// scene of the configuration
+    #[text_signature = "($self, cfg: Cfg, name: str, first: uint, last: uint, /)"]
+    pub fn render(&self, py: Python, cfg: &Cfg, name: String, first: usize, last: usize) {
+        let base = cfg.builder(name);
+        let timeline = self.contents.clone();
+        py.allow_threads(move || timeline.render(&base, first..last))
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Timeline {
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "Timeline {{
+    fps:     {},
+    shutter: {},
+    objects: {},
+}}",
+            self.contents.fps,
+            self.contents.shutter,
+            self.contents.objects.len(),
+        ))
+    }
+}
@@ -0,0 +1,524 @@
+use crate::external::Vec;
+use pyo3::prelude::*;
+use pyo3::{exceptions, PyNumberProtocol, PyObjectProtocol};
+use std::vec;
+
+use pytrace_core::internal;
+
+/// Check that rows given by Python form an n x n matrix
+fn check_square(rows: &[vec::Vec<f64>], n: usize) -> PyResult<()> {
+    if rows.len() != n || rows.iter().any(|r| r.len() != n) {
+        return Err(exceptions::ValueError::py_err(format!(
+            "Expected {} rows of {} values",
+            n, n
+        )));
+    }
+    Ok(())
+}
+
+fn singular() -> PyErr {
+    exceptions::ValueError::py_err("Matrix is not invertible")
+}
+
+#[pyclass]
+#[derive(Clone, Copy)]
+#[text_signature = "(rows: List[List[float]], /)"]
+pub struct Mat3 {
+    pub contents: internal::Mat3,
+}
+
+#[pymethods]
+impl Mat3 {
+    #[new]
+    pub fn new(rows: vec::Vec<vec::Vec<f64>>) -> PyResult<Self> {
+        check_square(&rows, 3)?;
+        let mut m = [[0.; 3]; 3];
+        for (i, row) in rows.iter().enumerate() {
+            m[i].copy_from_slice(row);
+        }
+        Ok(Self::from(internal::Mat3(m)))
+    }
+
+    #[staticmethod]
+    #[text_signature = "(/)"]
+    pub fn identity() -> Self {
+        Self::from(internal::Mat3::identity())
+    }
+
+    /// Scale by a different factor along each axis
+    #[staticmethod]
+    #[text_signature = "(factors: Vec, /)"]
+    pub fn diagonal(factors: Vec) -> Self {
+        Self::from(internal::Mat3::diagonal(factors.to_internal()))
+    }
+
+    /// Matrix whose columns are the images of the three axes
+    #[staticmethod]
+    #[text_signature = "(x: Vec, y: Vec, z: Vec, /)"]
+    pub fn from_columns(x: Vec, y: Vec, z: Vec) -> Self {
+        Self::from(internal::Mat3::from_columns(
+            x.to_internal(),
+            y.to_internal(),
+            z.to_internal(),
+        ))
+    }
+
+    /// Rotation of an angle (in degrees) around an axis
+    #[staticmethod]
+    #[text_signature = "(axis: Vec, angle: float, /)"]
+    pub fn rotation(axis: Vec, angle: f64) -> Self {
+        Self::from(internal::Mat3::rotation(axis.to_internal(), angle))
+    }
+
+    #[text_signature = "($self, j: int, /)"]
+    pub fn column(&self, j: usize) -> PyResult<Vec> {
+        if j < 3 {
+            Ok(Vec::from(self.contents.column(j)))
+        } else {
+            Err(exceptions::IndexError::py_err(format!("No column {}", j)))
+        }
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn transpose(&self) -> Self {
+        Self::from(self.contents.transpose())
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn determinant(&self) -> f64 {
+        self.contents.determinant()
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn inverse(&self) -> PyResult<Self> {
+        self.contents
+            .try_inverse()
+            .map(Self::from)
+            .ok_or_else(singular)
+    }
+
+    #[text_signature = "($self, v: Vec, /)"]
+    pub fn apply(&self, v: Vec) -> Vec {
+        Vec::from(self.contents.apply(v.to_internal()))
+    }
+
+    #[getter]
+    pub fn rows(&self) -> vec::Vec<vec::Vec<f64>> {
+        self.contents.0.iter().map(|row| row.to_vec()).collect()
+    }
+}
+
+impl Mat3 {
+    pub fn to_internal(self) -> internal::Mat3 {
+        self.contents
+    }
+
+    pub fn from(m: internal::Mat3) -> Self {
+        Self { contents: m }
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Mat3 {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!("Mat3({:?})", self.contents.0))
+    }
+}
+
+#[pyproto]
+impl PyNumberProtocol for Mat3 {
+    /// Composition: `a * b` applies b first, then a
+    fn __mul__(lhs: Mat3, rhs: Mat3) -> PyResult<Mat3> {
+        Ok(Mat3::from(lhs.contents * rhs.contents))
+    }
+}
+
+#[pyclass]
+#[derive(Clone, Copy)]
+#[text_signature = "(rows: List[List[float]], /)"]
+pub struct Mat4 {
+    pub contents: internal::Mat4,
+}
+
+#[pymethods]
+impl Mat4 {
+    #[new]
+    pub fn new(rows: vec::Vec<vec::Vec<f64>>) -> PyResult<Self> {
+        check_square(&rows, 4)?;
+        let mut m = [[0.; 4]; 4];
+        for (i, row) in rows.iter().enumerate() {
+            m[i].copy_from_slice(row);
+        }
+        Ok(Self::from(internal::Mat4(m)))
+    }
+
+    #[staticmethod]
+    #[text_signature = "(/)"]
+    pub fn identity() -> Self {
+        Self::from(internal::Mat4::identity())
+    }
+
+    /// Linear part m followed by a translation
+    #[staticmethod]
+    #[text_signature = "(m: Mat3, offset: Vec, /)"]
+    pub fn affine(m: Mat3, offset: Vec) -> Self {
+        Self::from(internal::Mat4::affine(m.contents, offset.to_internal()))
+    }
+
+    #[staticmethod]
+    #[text_signature = "(offset: Vec, /)"]
+    pub fn translation(offset: Vec) -> Self {
+        Self::from(internal::Mat4::translation(offset.to_internal()))
+    }
+
+    /// Scale by a different factor along each axis
+    #[staticmethod]
+    #[text_signature = "(factors: Vec, /)"]
+    pub fn scaling(factors: Vec) -> Self {
+        Self::from(internal::Mat4::scaling(factors.to_internal()))
+    }
+
+    /// Rotation of an angle (in degrees) around an axis going through the origin
+    #[staticmethod]
+    #[text_signature = "(axis: Vec, angle: float, /)"]
+    pub fn rotation(axis: Vec, angle: f64) -> Self {
+        Self::from(internal::Mat4::rotation(axis.to_internal(), angle))
+    }
+
+    /// Upper left 3x3 block, which acts on vectors
+    #[text_signature = "($self, /)"]
+    pub fn linear(&self) -> Mat3 {
+        Mat3::from(self.contents.linear())
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn translation_part(&self) -> Vec {
+        Vec::from(self.contents.translation_part())
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn transpose(&self) -> Self {
+        Self::from(self.contents.transpose())
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn inverse(&self) -> PyResult<Self> {
+        self.contents
+            .try_inverse()
+            .map(Self::from)
+            .ok_or_else(singular)
+    }
+
+    /// Image of a position, affected by translations
+    #[text_signature = "($self, p: Vec, /)"]
+    pub fn apply_point(&self, p: Vec) -> Vec {
+        Vec::from(self.contents.apply_point(p.to_internal()))
+    }
+
+    /// Image of a direction, not affected by translations
+    #[text_signature = "($self, v: Vec, /)"]
+    pub fn apply_vector(&self, v: Vec) -> Vec {
+        Vec::from(self.contents.apply_vector(v.to_internal()))
+    }
+
+    #[getter]
+    pub fn rows(&self) -> vec::Vec<vec::Vec<f64>> {
+        self.contents.0.iter().map(|row| row.to_vec()).collect()
+    }
+}
+
+impl Mat4 {
+    pub fn to_internal(self) -> internal::Mat4 {
+        self.contents
+    }
+
+    pub fn from(m: internal::Mat4) -> Self {
+        Self { contents: m }
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Mat4 {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!("Mat4({:?})", self.contents.0))
+    }
+}
+
+#[pyproto]
+impl PyNumberProtocol for Mat4 {
+    /// Composition: `a * b` applies b first, then a
+    fn __mul__(lhs: Mat4, rhs: Mat4) -> PyResult<Mat4> {
+        Ok(Mat4::from(lhs.contents * rhs.contents))
+    }
+}
+
+#[pyclass]
+#[derive(Clone, Copy)]
+#[text_signature = "(w: float, x: float, y: float, z: float, /)"]
+pub struct Quat {
+    #[pyo3(get, set)]
+    pub w: f64,
+    #[pyo3(get, set)]
+    pub x: f64,
+    #[pyo3(get, set)]
+    pub y: f64,
+    #[pyo3(get, set)]
+    pub z: f64,
+}
+
+#[pymethods]
+impl Quat {
+    #[new]
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Rotation that does nothing
+    #[staticmethod]
+    #[text_signature = "(/)"]
+    pub fn identity() -> Self {
+        Self::from(internal::Quat::identity())
+    }
+
+    /// Rotation of an angle (in degrees) around an axis
+    #[staticmethod]
+    #[text_signature = "(axis: Vec, angle: float, /)"]
+    pub fn axis_angle(axis: Vec, angle: f64) -> Self {
+        Self::from(internal::Quat::axis_angle(axis.to_internal(), angle))
+    }
+
+    /// Rotation given by Euler angles (in degrees): roll around z, then pitch
+    /// around x, then yaw around y
+    #[staticmethod]
+    #[text_signature = "(yaw: float, pitch: float, roll: float, /)"]
+    pub fn euler(yaw: f64, pitch: f64, roll: f64) -> Self {
+        Self::from(internal::Quat::euler(yaw, pitch, roll))
+    }
+
+    /// Axis and angle (in degrees) of the rotation
+    #[text_signature = "($self, /)"]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_axis_angle(&self) -> (Vec, f64) {
+        let (axis, angle) = self.to_internal().to_axis_angle();
+        (Vec::from(axis), angle)
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn normalize(&self) -> Self {
+        Self::from(self.to_internal().normalize())
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn conjugate(&self) -> Self {
+        Self::from(self.to_internal().conjugate())
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn inverse(&self) -> Self {
+        Self::from(self.to_internal().inverse())
+    }
+
+    #[text_signature = "($self, v: Vec, /)"]
+    pub fn rotate(&self, v: Vec) -> Vec {
+        Vec::from(self.to_internal().rotate(v.to_internal()))
+    }
+
+    /// Interpolate at constant speed between two rotations
+    #[text_signature = "($self, other: Quat, t: float, /)"]
+    pub fn slerp(&self, other: Quat, t: f64) -> Self {
+        Self::from(self.to_internal().slerp(other.to_internal(), t))
+    }
+}
+
+impl Quat {
+    pub fn to_internal(self) -> internal::Quat {
+        internal::Quat {
+            w: self.w,
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+
+    pub fn from(q: internal::Quat) -> Self {
+        Self {
+            w: q.w,
+            x: q.x,
+            y: q.y,
+            z: q.z,
+        }
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Quat {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "Quat {{ w: {}, x: {}, y: {}, z: {} }}",
+            self.w, self.x, self.y, self.z
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        Ok(format!(
+            "Quaternion({} + {}i + {}j + {}k)",
+            self.w, self.x, self.y, self.z
+        ))
+    }
+}
+
+#[pyproto]
+impl PyNumberProtocol for Quat {
+    fn __mul__(lhs: Quat, rhs: Quat) -> PyResult<Quat> {
+        Ok(Quat::from(lhs.to_internal() * rhs.to_internal()))
+    }
+}
+
+#[pyclass]
+#[derive(Clone, Copy)]
+#[text_signature = "(/)"]
+pub struct Transform {
+    pub contents: internal::Transform,
+}
+
+#[pymethods]
+impl Transform {
+    /// Identity transformation
+    #[new]
+    pub fn new() -> Self {
+        Self::from(internal::Transform::identity())
+    }
+
+    #[staticmethod]
+    #[text_signature = "(offset: Vec, /)"]
+    pub fn translation(offset: Vec) -> Self {
+        Self::from(internal::Transform::translation(offset.to_internal()))
+    }
+
+    /// Scale by a different factor along each axis, relative to the origin
+    #[staticmethod]
+    #[text_signature = "(factors: Vec, /)"]
+    pub fn scaling(factors: Vec) -> Self {
+        Self::from(internal::Transform::scaling(factors.to_internal()))
+    }
+
+    /// Rotation of an angle (in degrees) around an axis going through the origin
+    #[staticmethod]
+    #[text_signature = "(axis: Vec, angle: float, /)"]
+    pub fn rotation(axis: Vec, angle: f64) -> Self {
+        Self::from(internal::Transform::rotation(axis.to_internal(), angle))
+    }
+
+    /// Rotation given by Euler angles (in degrees), see `Quat.euler`
+    #[staticmethod]
+    #[text_signature = "(yaw: float, pitch: float, roll: float, /)"]
+    pub fn euler(yaw: f64, pitch: f64, roll: f64) -> Self {
+        Self::from(internal::Transform::euler(yaw, pitch, roll))
+    }
+
+    #[staticmethod]
+    #[text_signature = "(q: Quat, /)"]
+    pub fn from_quat(q: Quat) -> Self {
+        Self::from(internal::Transform::from_quat(q.to_internal()))
+    }
+
+    /// Arbitrary invertible matrix
+    #[staticmethod]
+    #[text_signature = "(m: Mat4, /)"]
+    pub fn from_matrix(m: Mat4) -> PyResult<Self> {
+        if m.contents.try_inverse().is_none() {
+            return Err(singular());
+        }
+        Ok(Self::from(internal::Transform::new(m.contents)))
+    }
+
+    /// Place an object at eye, with its z axis pointing away from target
+    #[staticmethod]
+    #[text_signature = "(eye: Vec, target: Vec, up: Vec, /)"]
+    pub fn look_at(eye: Vec, target: Vec, up: Vec) -> Self {
+        Self::from(internal::Transform::look_at(
+            eye.to_internal(),
+            target.to_internal(),
+            up.to_internal(),
+        ))
+    }
+
+    /// Same transformation, applied around center instead of the origin
+    #[text_signature = "($self, center: Vec, /)"]
+    pub fn around(&self, center: Vec) -> Self {
+        Self::from(self.contents.around(center.to_internal()))
+    }
+
+    #[text_signature = "($self, /)"]
+    pub fn invert(&self) -> Self {
+        Self::from(self.contents.invert())
+    }
+
+    #[text_signature = "($self, p: Vec, /)"]
+    pub fn apply_point(&self, p: Vec) -> Vec {
+        Vec::from(self.contents.apply_point(p.to_internal()))
+    }
+
+    /// Image of a direction, not affected by translations
+    #[text_signature = "($self, v: Vec, /)"]
+    pub fn apply_vector(&self, v: Vec) -> Vec {
+        Vec::from(self.contents.apply_vector(v.to_internal()))
+    }
+
+    /// Image of a normal, which remains orthogonal to the surface (not normalized)
+    #[text_signature = "($self, n: Vec, /)"]
+    pub fn apply_normal(&self, n: Vec) -> Vec {
+        Vec::from(self.contents.apply_normal(n.to_internal()))
+    }
+
+    /// Rows of the 4x4 matrix
+    #[getter]
+    pub fn matrix(&self) -> vec::Vec<vec::Vec<f64>> {
+        self.contents
+            .matrix
+            .0
+            .iter()
+            .map(|row| row.to_vec())
+            .collect()
+    }
+}
+
+impl Transform {
+    pub fn to_internal(self) -> internal::Transform {
+        self.contents
+    }
+
+    pub fn from(t: internal::Transform) -> Self {
+        Self { contents: t }
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Transform {
+    fn __repr__(self) -> PyResult<String> {
+        Ok(format!(
+            "Transform {{ matrix: {:?} }}",
+            self.contents.matrix.0
+        ))
+    }
+
+    fn __str__(self) -> PyResult<String> {
+        let rows = self
+            .contents
+            .matrix
+            .0
+            .iter()
+            .map(|row| format!("{:?}", row))
+            .collect::<vec::Vec<_>>()
+            .join("\n          ");
+        Ok(format!("Transform {}", rows))
+    }
+}
+
+#[pyproto]
+impl PyNumberProtocol for Transform {
+    /// Composition: `a * b` applies b first, then a
+    fn __mul__(lhs: Transform, rhs: Transform) -> PyResult<Transform> {
+        Ok(Transform::from(lhs.contents * rhs.contents))
+    }
+}
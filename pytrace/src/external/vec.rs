@@ -16,6 +16,8 @@ pub struct Vec {
     pub z: f64,
 }
 
+fingerprint!(Vec { x, y, z });
+
 #[pymethods]
 impl Vec {
     #[new]
@@ -7,7 +7,14 @@ mod external;
 fn pytrace(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<external::Cfg>().unwrap();
     m.add_class::<external::Camera>().unwrap();
+    m.add_class::<external::PhysicalCamera>().unwrap();
+    m.add_class::<external::CameraPath>().unwrap();
     m.add_class::<external::Vec>().unwrap();
+    m.add_class::<external::Mat3>().unwrap();
+    m.add_class::<external::Mat4>().unwrap();
+    m.add_class::<external::Quat>().unwrap();
+    m.add_class::<external::Transform>().unwrap();
+    m.add_class::<external::Timeline>().unwrap();
     m.add_class::<external::Sky>().unwrap();
     m.add_class::<external::RGB>().unwrap();
     m.add_class::<external::Texture>().unwrap();
@@ -21,6 +28,16 @@ fn pytrace(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<external::Cylinder>().unwrap();
     m.add_class::<external::EmptyCone>().unwrap();
     m.add_class::<external::Cone>().unwrap();
+    m.add_class::<external::Torus>().unwrap();
+    m.add_class::<external::Ellipsoid>().unwrap();
+    m.add_class::<external::Paraboloid>().unwrap();
+    m.add_class::<external::Hyperboloid>().unwrap();
+    m.add_class::<external::Implicit>().unwrap();
+    m.add_class::<external::HeightField>().unwrap();
+    m.add_class::<external::Lathe>().unwrap();
+    m.add_class::<external::Extrusion>().unwrap();
+    m.add_class::<external::BezierPatch>().unwrap();
+    m.add_class::<external::Tube>().unwrap();
     m.add_class::<external::Axes>().unwrap();
     m.add_class::<external::Cradle>().unwrap();
     Ok(())
@@ -15,15 +15,6 @@ fn build_world() -> Builder {
     let wth = 200; // width in pixels
     let hgt = 200; // height in pixels
     let iter = 20; // number of samples per pixel
-    let cam = Camera::new_relative(
-        Vec3(0.37, 1.0, 0.0),   // target
-        0.,                   // angle (degrees)
-        0.0,                    // rise (degrees)
-        0.1,                   // distance (meters),
-        0.0,                     // tilt (degrees)
-        50.0,                    // aperture (degrees)
-        wth as f64 / hgt as f64, // aspect ratio
-    );
     let sky = Sky::uniform(RGB(0.5, 0.5, 0.5));
     let mut world = World::new();
 
@@ -38,6 +29,17 @@ fn build_world() -> Builder {
 
     world.push_vec(cradle);
 
+    // Target and distance are chosen so that the whole scene is in view
+    let cam = Camera::frame_object(
+        world.bounds().unwrap(),
+        0.,                      // angle (degrees)
+        0.0,                     // rise (degrees)
+        0.0,                     // tilt (degrees)
+        50.0,                    // aperture (degrees)
+        wth as f64 / hgt as f64, // aspect ratio
+        Projection::Perspective,
+    );
+
     Builder {
         name: String::from("newt"),
         silent: false,
@@ -45,8 +47,11 @@ fn build_world() -> Builder {
         wth,
         iter,
         cam,
+        stereo: None,
+        exposure: 1.,
         world,
         sky,
         nbsync: 5,
+        patch: None,
     }
 }